@@ -0,0 +1,212 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Online backups through `backup:` cursors.
+//!
+//! A full backup copies every file the backup cursor lists. An incremental
+//! backup is opened with `BackupConfig::incremental_source` on top of a copy of
+//! the source backup, and copies only the blocks modified since then:
+//!
+//! ```ignore
+//! let config = BackupConfig::new().incremental_id("ID1");
+//! session.open_backup(&config)?.copy_to("backup")?;
+//!
+//! let config = BackupConfig::new()
+//!     .incremental_source("ID1")
+//!     .incremental_id("ID2");
+//! session.open_backup(&config)?.copy_changes_to("backup")?;
+//! ```
+
+use crate::config::quote;
+use crate::error::Result;
+use crate::{Cursor, Session};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use wiredtiger_sys::{WT_BACKUP_FILE, WT_BACKUP_RANGE};
+
+/// A part of a file modified since the source of an incremental backup.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// The whole file must be copied, e.g. because it is new.
+    File,
+    /// `size` bytes starting at `offset` must be copied.
+    Range { offset: u64, size: u64 },
+}
+
+/// An open backup cursor. The files it lists are not modified by checkpoints
+/// until it is closed.
+pub struct Backup<'s> {
+    session: &'s Session<'s>,
+    cursor: Cursor<'s>,
+}
+
+impl<'s> Backup<'s> {
+    pub(crate) fn new(session: &'s Session<'s>, cursor: Cursor<'s>) -> Self {
+        Self { session, cursor }
+    }
+
+    /// The names of the files to copy, relative to the database home.
+    pub fn files(&mut self) -> Result<Vec<String>> {
+        self.cursor.reset()?;
+        let mut files = Vec::new();
+        while self.cursor.next()? {
+            files.push(self.cursor.get_key::<String>()?);
+        }
+        Ok(files)
+    }
+
+    /// The blocks of `file` modified since the source backup. Only valid for
+    /// incremental backups.
+    pub fn blocks(&self, file: &str) -> Result<Vec<Block>> {
+        let config = format!("incremental=(file={})", quote(file));
+        let mut cursor = self.session.open_duplicate_cursor(&self.cursor, config)?;
+        let mut blocks = Vec::new();
+        while cursor.next()? {
+            let (offset, size, kind) = cursor.get_key::<(i64, i64, i64)>()?;
+            match kind as u32 {
+                WT_BACKUP_FILE => blocks.push(Block::File),
+                WT_BACKUP_RANGE => blocks.push(Block::Range {
+                    offset: offset as u64,
+                    size: size as u64,
+                }),
+                _ => (),
+            }
+        }
+        cursor.close()?;
+        Ok(blocks)
+    }
+
+    /// Copy every file to the directory `target`, creating it if needed.
+    ///
+    /// Returns the names of the copied files.
+    pub fn copy_to<P: AsRef<Path>>(&mut self, target: P) -> Result<Vec<String>> {
+        let target = target.as_ref();
+        fs::create_dir_all(target)?;
+        let files = self.files()?;
+        for file in &files {
+            fs::copy(self.source(file)?, target.join(file))?;
+        }
+        Ok(files)
+    }
+
+    /// Copy the blocks modified since the source backup to the directory
+    /// `target`, which holds a copy of the source backup.
+    ///
+    /// Returns the names of the listed files. Files of the target missing from
+    /// them were dropped and can be removed.
+    pub fn copy_changes_to<P: AsRef<Path>>(&mut self, target: P) -> Result<Vec<String>> {
+        let target = target.as_ref();
+        fs::create_dir_all(target)?;
+        let files = self.files()?;
+        for file in &files {
+            let source = self.source(file)?;
+            for block in self.blocks(file)? {
+                match block {
+                    Block::File => {
+                        fs::copy(&source, target.join(file))?;
+                    }
+                    Block::Range { offset, size } => {
+                        copy_range(&source, &target.join(file), offset, size)?;
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    #[inline]
+    pub fn close(mut self) -> Result<()> {
+        self.cursor.close()
+    }
+
+    fn source(&self, file: &str) -> Result<PathBuf> {
+        Ok(self.session.home()?.join(file))
+    }
+}
+
+/// Copy `size` bytes at `offset` of `source` to the same offset of `target`.
+fn copy_range(source: &Path, target: &Path, offset: u64, size: u64) -> io::Result<()> {
+    let mut source = File::open(source)?;
+    let mut target = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(target)?;
+    source.seek(SeekFrom::Start(offset))?;
+    target.seek(SeekFrom::Start(offset))?;
+    io::copy(&mut source.take(size), &mut target)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::BackupConfig;
+    use crate::CheckpointConfig;
+    use crate::Connection;
+    use test_utils;
+
+    fn insert(conn: &Connection, key: &str, value: &str) {
+        let session = conn.open_session("").unwrap();
+        let mut cursor = session.open_cursor("table:backup", "").unwrap();
+        cursor.set_key(key).unwrap();
+        cursor.set_value(value).unwrap();
+        cursor.insert().unwrap();
+        cursor.close().unwrap();
+        session.checkpoint(&CheckpointConfig::new()).unwrap();
+    }
+
+    fn get(home: &str, key: &str) -> Option<String> {
+        let conn = Connection::open(home, "").unwrap();
+        let session = conn.open_session("").unwrap();
+        let mut cursor = session.open_cursor("table:backup", "").unwrap();
+        cursor.set_key(key).unwrap();
+        if cursor.search().unwrap() {
+            Some(cursor.get_value().unwrap())
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn test_backup() {
+        let home = "target/wt_backup";
+        let target = "target/wt_backup_target";
+        test_utils::make_work_dir(home, true);
+        test_utils::make_work_dir(target, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:backup", "key_format=S,value_format=S")
+            .unwrap();
+        insert(&conn, "a", "1");
+
+        let config = BackupConfig::new().incremental_id("ID1");
+        let mut backup = session.open_backup(&config).unwrap();
+        let files = backup.copy_to(target).unwrap();
+        assert!(files.iter().any(|f| f == "backup.wt"));
+        backup.close().unwrap();
+
+        insert(&conn, "b", "2");
+        let config = BackupConfig::new()
+            .incremental_source("ID1")
+            .incremental_id("ID2");
+        let mut backup = session.open_backup(&config).unwrap();
+        backup.copy_changes_to(target).unwrap();
+        backup.close().unwrap();
+
+        assert_eq!(get(target, "a"), Some("1".to_string()));
+        assert_eq!(get(target, "b"), Some("2".to_string()));
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Changes of tables, read from the log.
+//!
+//! The events of a transaction are consecutive and share the LSN of its commit
+//! record. A consumer saves the LSN of the last transaction it handled and
+//! continues after it with `ChangeStream::resume_after`, as long as the log
+//! still holds it.
+//!
+//! Keys and values are packed with the formats of the table, or of the column
+//! group for tables with several column groups. Changes of indices are not
+//! reported.
+
+use crate::error::{Error, Result};
+use crate::log::{LogOp, LogReader, LogRecord, Lsn};
+use crate::metadata::Schema;
+use crate::{uri, Session};
+use std::collections::{HashMap, HashSet};
+
+/// The kind of a change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChangeOp {
+    Put,
+    Remove,
+    /// A partial update, with the modifications packed in the value.
+    Modify,
+    /// A range removal, with the start and stop keys in the key and the
+    /// value.
+    Truncate,
+}
+
+impl ChangeOp {
+    fn of(op: LogOp) -> Option<Self> {
+        match op {
+            LogOp::RowPut | LogOp::ColPut => Some(ChangeOp::Put),
+            LogOp::RowRemove | LogOp::ColRemove => Some(ChangeOp::Remove),
+            LogOp::RowModify | LogOp::ColModify => Some(ChangeOp::Modify),
+            LogOp::RowTruncate | LogOp::ColTruncate => Some(ChangeOp::Truncate),
+            _ => None,
+        }
+    }
+}
+
+/// A committed change of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    pub lsn: Lsn,
+    /// The URI of the table, e.g. `table:name`, or of the file for files
+    /// created directly.
+    pub table: String,
+    pub op: ChangeOp,
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub txn_id: u64,
+}
+
+/// Iterates over the changes of the tables in the log, oldest first.
+pub struct ChangeStream<'s> {
+    session: &'s Session<'s>,
+    reader: LogReader<'s>,
+    /// The tables of the file IDs.
+    tables: HashMap<u32, String>,
+    /// File IDs of indices and internal files.
+    ignored: HashSet<u32>,
+    /// Skip the records at this LSN, after `resume_after`.
+    skip: Option<Lsn>,
+}
+
+impl<'s> ChangeStream<'s> {
+    pub(crate) fn new(session: &'s Session<'s>) -> Result<Self> {
+        let mut stream = Self {
+            session,
+            reader: session.open_log_reader()?,
+            tables: HashMap::new(),
+            ignored: HashSet::new(),
+            skip: None,
+        };
+        stream.refresh()?;
+        Ok(stream)
+    }
+
+    /// Continue with the changes after the transaction at `lsn`.
+    ///
+    /// Returns `Error::NotFound` if the log does not hold `lsn` anymore.
+    pub fn resume_after(&mut self, lsn: Lsn) -> Result<()> {
+        if !self.reader.seek(lsn)? {
+            return Err(Error::NotFound);
+        }
+        self.skip = Some(lsn);
+        Ok(())
+    }
+
+    /// Read the next change, or `None` at the end of the log.
+    pub fn next_event(&mut self) -> Result<Option<ChangeEvent>> {
+        while let Some(entry) = self.reader.next_entry()? {
+            if self.skip == Some(entry.lsn) {
+                continue;
+            }
+            self.skip = None;
+            let (txn_id, op, file_id, key, value) = match entry.record {
+                LogRecord::Commit {
+                    txn_id,
+                    op,
+                    file_id,
+                    key,
+                    value,
+                } => (txn_id, op, file_id, key, value),
+                _ => continue,
+            };
+            let op = match ChangeOp::of(op) {
+                Some(op) => op,
+                None => continue,
+            };
+            if let Some(table) = self.table(file_id)? {
+                return Ok(Some(ChangeEvent {
+                    lsn: entry.lsn,
+                    table,
+                    op,
+                    key,
+                    value,
+                    txn_id,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    #[inline]
+    pub fn close(self) -> Result<()> {
+        self.reader.close()
+    }
+
+    /// The table of the file `id`, reading the metadata again for files
+    /// created since it was last read.
+    fn table(&mut self, id: u32) -> Result<Option<String>> {
+        if self.ignored.contains(&id) {
+            return Ok(None);
+        }
+        if !self.tables.contains_key(&id) {
+            self.refresh()?;
+        }
+        match self.tables.get(&id) {
+            Some(table) => Ok(Some(table.clone())),
+            None => {
+                self.ignored.insert(id);
+                Ok(None)
+            }
+        }
+    }
+
+    fn refresh(&mut self) -> Result<()> {
+        let schema = Schema::new(self.session.list_objects()?);
+        let mut ids = HashMap::new();
+        for file in &schema.files {
+            if let Some(id) = file.id {
+                let source = format!("file:{}", file.name);
+                self.tables.insert(id, source.clone());
+                ids.insert(source, id);
+            }
+        }
+        for table in &schema.tables {
+            for colgroup in &table.colgroups {
+                if let Some(id) = ids.get(&colgroup.source) {
+                    self.tables.insert(*id, uri::table(&table.table.name));
+                }
+            }
+            for index in &table.indices {
+                if let Some(id) = ids.get(&index.source) {
+                    self.tables.remove(id);
+                    self.ignored.insert(*id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'s> Iterator for ChangeStream<'s> {
+    type Item = Result<ChangeEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pack, Connection};
+    use test_utils;
+
+    fn put(session: &Session, key: &str, value: &str) {
+        let mut cursor = session.open_cursor("table:changes", "").unwrap();
+        cursor.set_key(key).unwrap();
+        cursor.set_value(value).unwrap();
+        cursor.insert().unwrap();
+        cursor.close().unwrap();
+    }
+
+    #[test]
+    fn test_change_stream() {
+        let home = "target/wt_change_stream";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create,log=(enabled=true)").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:changes", "key_format=S,value_format=S,columns=(k,v)")
+            .unwrap();
+        session.create("index:changes:by_v", "columns=(v)").unwrap();
+        put(&session, "a", "1");
+
+        let mut stream = session.open_change_stream().unwrap();
+        let events = stream.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.table, "table:changes");
+        assert_eq!(event.op, ChangeOp::Put);
+        assert_eq!(pack::unpack::<String>("S", &event.key).unwrap(), "a");
+        assert_eq!(pack::unpack::<String>("S", &event.value).unwrap(), "1");
+
+        put(&session, "b", "2");
+        let mut stream = session.open_change_stream().unwrap();
+        stream.resume_after(event.lsn).unwrap();
+        let events = stream.collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(pack::unpack::<String>("S", &events[0].key).unwrap(), "b");
+    }
+}
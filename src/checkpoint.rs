@@ -0,0 +1,134 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Options of checkpoints.
+
+use crate::config::{list, quote};
+use std::fmt::Formatter;
+
+/// The name of the most recent unnamed checkpoint.
+pub const LAST_CHECKPOINT: &str = "WiredTigerCheckpoint";
+
+/// Options of `Session::checkpoint`.
+///
+/// The default options checkpoint all objects without a name.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointConfig {
+    force: bool,
+    name: Option<String>,
+    target: Vec<String>,
+    drop: Vec<String>,
+    use_timestamp: Option<bool>,
+}
+
+impl CheckpointConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checkpoint objects even if they have not changed since the last
+    /// checkpoint.
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Name the checkpoint, so cursors can be opened on it later.
+    pub fn name<N: Into<String>>(mut self, name: N) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Only checkpoint the object `uri`. May be called several times.
+    pub fn target<U: Into<String>>(mut self, uri: U) -> Self {
+        self.target.push(uri.into());
+        self
+    }
+
+    /// Drop the checkpoint `name`.
+    pub fn drop_checkpoint<N: AsRef<str>>(mut self, name: N) -> Self {
+        self.drop.push(quote(name.as_ref()));
+        self
+    }
+
+    /// Drop all checkpoints from `name` on, inclusive.
+    pub fn drop_from<N: AsRef<str>>(mut self, name: N) -> Self {
+        self.drop.push(format!("from={}", quote(name.as_ref())));
+        self
+    }
+
+    /// Drop all checkpoints up to `name`, inclusive.
+    pub fn drop_to<N: AsRef<str>>(mut self, name: N) -> Self {
+        self.drop.push(format!("to={}", quote(name.as_ref())));
+        self
+    }
+
+    /// Drop all named checkpoints.
+    pub fn drop_all(mut self) -> Self {
+        self.drop.push("from=all".to_string());
+        self
+    }
+
+    /// Whether to checkpoint at the stable timestamp. WiredTiger does by
+    /// default.
+    pub fn use_timestamp(mut self, use_timestamp: bool) -> Self {
+        self.use_timestamp = Some(use_timestamp);
+        self
+    }
+}
+
+impl std::fmt::Display for CheckpointConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut items = Vec::new();
+        if self.force {
+            items.push("force=true".to_string());
+        }
+        if let Some(name) = &self.name {
+            items.push(format!("name={}", quote(name)));
+        }
+        if !self.target.is_empty() {
+            let target = list(self.target.iter().map(|uri| quote(uri)));
+            items.push(format!("target={}", target));
+        }
+        if !self.drop.is_empty() {
+            items.push(format!("drop={}", list(&self.drop)));
+        }
+        if let Some(use_timestamp) = self.use_timestamp {
+            items.push(format!("use_timestamp={}", use_timestamp));
+        }
+        write!(f, "{}", items.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_config() {
+        assert_eq!(CheckpointConfig::new().to_string(), "");
+        let config = CheckpointConfig::new()
+            .force(true)
+            .name("daily")
+            .target("table:a")
+            .target("table:b")
+            .drop_to("hourly")
+            .use_timestamp(false);
+        assert_eq!(
+            config.to_string(),
+            "force=true,name=\"daily\",target=(\"table:a\",\"table:b\"),\
+             drop=(to=\"hourly\"),use_timestamp=false"
+        );
+    }
+}
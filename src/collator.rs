@@ -0,0 +1,181 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Key orderings implemented in Rust.
+//!
+//! A collator is registered with `Connection::add_collator` and used by the
+//! tables and indices created with `collator=<name>`, which must be registered
+//! again every time the database is opened.
+
+use crate::error::catch_callback;
+use crate::pack::item_as_slice;
+use std::cmp::Ordering;
+use std::os::raw::c_int;
+use wiredtiger_sys::{WT_COLLATOR, WT_ITEM, WT_SESSION};
+
+/// Compares keys in their packed form.
+pub trait Collator: Send + Sync + 'static {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering;
+}
+
+impl<F> Collator for F
+where
+    F: Fn(&[u8], &[u8]) -> Ordering + Send + Sync + 'static,
+{
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// A `WT_COLLATOR` dispatching to a `Collator`. WiredTiger owns it once it is
+/// registered, and frees it with `terminate` when the connection is closed.
+#[repr(C)]
+pub(crate) struct CollatorAdapter {
+    // Must be the first field, WiredTiger passes a pointer to it.
+    base: WT_COLLATOR,
+    collator: Box<dyn Collator>,
+}
+
+impl CollatorAdapter {
+    pub(crate) fn new<C: Collator>(collator: C) -> Box<Self> {
+        Box::new(Self {
+            base: WT_COLLATOR {
+                compare: Some(compare),
+                customize: None,
+                terminate: Some(terminate),
+            },
+            collator: Box::new(collator),
+        })
+    }
+}
+
+unsafe extern "C" fn compare(
+    collator: *mut WT_COLLATOR,
+    _session: *mut WT_SESSION,
+    key1: *const WT_ITEM,
+    key2: *const WT_ITEM,
+    cmp: *mut c_int,
+) -> c_int {
+    let adapter = &*(collator as *const CollatorAdapter);
+    let (a, b) = (item_as_slice(&*key1), item_as_slice(&*key2));
+    catch_callback("compare keys", || {
+        *cmp = adapter.collator.compare(a, b) as c_int;
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn terminate(collator: *mut WT_COLLATOR, _session: *mut WT_SESSION) -> c_int {
+    catch_callback("drop collator", || {
+        drop(Box::from_raw(collator as *mut CollatorAdapter));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::new_item;
+    use crate::Connection;
+    use std::ptr;
+    use test_utils;
+    use wiredtiger_sys::WT_ERROR;
+
+    unsafe fn call(adapter: &mut CollatorAdapter, a: &[u8], b: &[u8]) -> (c_int, c_int) {
+        let (a, b) = (new_item(a), new_item(b));
+        let mut cmp = 0;
+        let ret = compare(&mut adapter.base, ptr::null_mut(), &a, &b, &mut cmp);
+        (ret, cmp)
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut adapter = CollatorAdapter::new(|a: &[u8], b: &[u8]| b.cmp(a));
+        unsafe {
+            assert_eq!(call(&mut adapter, b"a", b"b"), (0, 1));
+            assert_eq!(call(&mut adapter, b"b", b"a"), (0, -1));
+            assert_eq!(call(&mut adapter, b"a", b"a"), (0, 0));
+        }
+
+        let mut adapter =
+            CollatorAdapter::new(|_: &[u8], _: &[u8]| -> Ordering { panic!("no order") });
+        unsafe {
+            assert_eq!(call(&mut adapter, b"a", b"b").0, WT_ERROR);
+        }
+    }
+
+    #[test]
+    fn test_collator() {
+        let home = "target/wt_collator";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        conn.add_collator("reverse", |a: &[u8], b: &[u8]| b.cmp(a))
+            .unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create(
+                "table:collator",
+                "key_format=S,value_format=S,collator=reverse",
+            )
+            .unwrap();
+        let mut cursor = session.open_cursor("table:collator", "").unwrap();
+        for key in &["a", "c", "b"] {
+            cursor.set_key(*key).unwrap();
+            cursor.set_value(*key).unwrap();
+            cursor.insert().unwrap();
+        }
+        let keys = cursor
+            .iter::<String, String>()
+            .unwrap()
+            .map(|record| record.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_collator_range() {
+        let home = "target/wt_collator_range";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        conn.add_collator("reverse", |a: &[u8], b: &[u8]| b.cmp(a))
+            .unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create(
+                "table:collator_range",
+                "key_format=S,value_format=S,collator=reverse",
+            )
+            .unwrap();
+        let mut cursor = session.open_cursor("table:collator_range", "").unwrap();
+        for key in &["a", "b", "c", "d", "e"] {
+            cursor.set_key(*key).unwrap();
+            cursor.set_value(*key).unwrap();
+            cursor.insert().unwrap();
+        }
+
+        // In the table's order "d" comes before "b".
+        let keys: Vec<String> = cursor
+            .range::<String, String, _, _>("d"..="b")
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec!["d", "c", "b"]);
+
+        let keys: Vec<String> = cursor
+            .range_rev::<String, String, _, _>("d".."a")
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec!["b", "c", "d"]);
+    }
+}
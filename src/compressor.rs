@@ -0,0 +1,406 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Block compression implemented in Rust.
+//!
+//! Tables created with `block_compressor=<name>` use the compressor added
+//! under that name with `Connection::add_compressor`. Compressors are not
+//! stored in the database, so opening such a table after a restart requires
+//! adding it again first.
+
+use crate::error::{catch_callback, Error, Result, EINVAL};
+use std::os::raw::c_int;
+use std::slice;
+use wiredtiger_sys::{WT_COMPRESSOR, WT_SESSION};
+
+/// Compresses and decompresses blocks.
+pub trait Compressor: Send + Sync + 'static {
+    /// Compress `src` into `dst`, which holds `pre_size(src)` bytes.
+    ///
+    /// Returns the size of the compressed block, or `None` if it does not fit
+    /// in `dst`, in which case the block is stored uncompressed.
+    fn compress(&self, src: &[u8], dst: &mut [u8]) -> Result<Option<usize>>;
+
+    /// Decompress `src` into `dst`, which holds at least the size of the
+    /// block before compression. Returns the size of the decompressed block.
+    fn decompress(&self, src: &[u8], dst: &mut [u8]) -> Result<usize>;
+
+    /// The size of the buffer to compress `src` into.
+    fn pre_size(&self, src: &[u8]) -> usize {
+        src.len()
+    }
+
+    /// Called when the connection is closed.
+    fn terminate(&self) {}
+}
+
+/// Laid out like `CollatorAdapter`: the callbacks receive a pointer to `base`
+/// and cast it back to the adapter.
+#[repr(C)]
+pub(crate) struct CompressorAdapter {
+    base: WT_COMPRESSOR,
+    compressor: Box<dyn Compressor>,
+}
+
+impl CompressorAdapter {
+    pub(crate) fn new<C: Compressor>(compressor: C) -> Box<Self> {
+        Box::new(Self {
+            base: WT_COMPRESSOR {
+                compress: Some(compress),
+                decompress: Some(decompress),
+                pre_size: Some(pre_size),
+                terminate: Some(terminate),
+            },
+            compressor: Box::new(compressor),
+        })
+    }
+}
+
+/// Run `f` with the `Compressor` behind `compressor`.
+unsafe fn dispatch<F>(compressor: *mut WT_COMPRESSOR, operation: &str, f: F) -> c_int
+where
+    F: FnOnce(&dyn Compressor) -> Result<()>,
+{
+    let adapter = &*(compressor as *const CompressorAdapter);
+    catch_callback(operation, || f(adapter.compressor.as_ref()))
+}
+
+/// View a buffer passed by WiredTiger as a slice, which may be null when empty.
+unsafe fn buffer<'a>(data: *mut u8, len: usize) -> &'a [u8] {
+    if data.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// The mutable counterpart of `buffer`.
+unsafe fn buffer_mut<'a>(data: *mut u8, len: usize) -> &'a mut [u8] {
+    if data.is_null() || len == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(data, len)
+    }
+}
+
+/// Check that a length returned by a `Compressor` fits in its buffer, as
+/// WiredTiger trusts it.
+fn checked_len(len: usize, dst: &[u8]) -> Result<usize> {
+    if len <= dst.len() {
+        Ok(len)
+    } else {
+        Err(Error::System(EINVAL))
+    }
+}
+
+unsafe extern "C" fn compress(
+    compressor: *mut WT_COMPRESSOR,
+    _session: *mut WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_len: usize,
+    result_lenp: *mut usize,
+    compression_failed: *mut c_int,
+) -> c_int {
+    let src = buffer(src, src_len);
+    let dst = buffer_mut(dst, dst_len);
+    dispatch(compressor, "compress block", |compressor| {
+        match compressor.compress(src, dst)? {
+            Some(len) => {
+                *result_lenp = checked_len(len, dst)?;
+                *compression_failed = 0;
+            }
+            None => *compression_failed = 1,
+        }
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn decompress(
+    compressor: *mut WT_COMPRESSOR,
+    _session: *mut WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    dst: *mut u8,
+    dst_len: usize,
+    result_lenp: *mut usize,
+) -> c_int {
+    let src = buffer(src, src_len);
+    let dst = buffer_mut(dst, dst_len);
+    dispatch(compressor, "decompress block", |compressor| {
+        let len = compressor.decompress(src, dst)?;
+        *result_lenp = checked_len(len, dst)?;
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn pre_size(
+    compressor: *mut WT_COMPRESSOR,
+    _session: *mut WT_SESSION,
+    src: *mut u8,
+    src_len: usize,
+    result_lenp: *mut usize,
+) -> c_int {
+    let src = buffer(src, src_len);
+    dispatch(compressor, "size block", |compressor| {
+        *result_lenp = compressor.pre_size(src);
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn terminate(compressor: *mut WT_COMPRESSOR, _session: *mut WT_SESSION) -> c_int {
+    let ret = dispatch(compressor, "terminate compressor", |compressor| {
+        compressor.terminate();
+        Ok(())
+    });
+    let dropped = catch_callback("drop compressor", || {
+        drop(Box::from_raw(compressor as *mut CompressorAdapter));
+        Ok(())
+    });
+    if ret != 0 {
+        ret
+    } else {
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Connection;
+    use std::ptr;
+    use test_utils;
+    use wiredtiger_sys::WT_ERROR;
+
+    /// Run-length encoding, as pairs of a count and a byte.
+    struct Rle;
+
+    impl Compressor for Rle {
+        fn compress(&self, src: &[u8], dst: &mut [u8]) -> Result<Option<usize>> {
+            let mut len = 0;
+            let mut rest = src;
+            while let Some(&byte) = rest.first() {
+                let count = rest.iter().take(255).take_while(|&&b| b == byte).count();
+                if len + 2 > dst.len() {
+                    return Ok(None);
+                }
+                dst[len] = count as u8;
+                dst[len + 1] = byte;
+                len += 2;
+                rest = &rest[count..];
+            }
+            Ok(Some(len))
+        }
+
+        fn decompress(&self, src: &[u8], dst: &mut [u8]) -> Result<usize> {
+            let mut len = 0;
+            for pair in src.chunks(2) {
+                let (count, byte) = match pair {
+                    [count, byte] => (*count as usize, *byte),
+                    _ => return Err(Error::System(EINVAL)),
+                };
+                if len + count > dst.len() {
+                    return Err(Error::System(EINVAL));
+                }
+                dst[len..len + count].iter_mut().for_each(|b| *b = byte);
+                len += count;
+            }
+            Ok(len)
+        }
+
+        fn pre_size(&self, src: &[u8]) -> usize {
+            src.len() * 2
+        }
+    }
+
+    struct Panicking;
+
+    impl Compressor for Panicking {
+        fn compress(&self, _: &[u8], _: &mut [u8]) -> Result<Option<usize>> {
+            panic!("cannot compress")
+        }
+
+        fn decompress(&self, _: &[u8], _: &mut [u8]) -> Result<usize> {
+            Err(Error::System(EINVAL))
+        }
+    }
+
+    /// Claims blocks larger than its buffers.
+    struct Lying;
+
+    impl Compressor for Lying {
+        fn compress(&self, _: &[u8], dst: &mut [u8]) -> Result<Option<usize>> {
+            Ok(Some(dst.len() + 1))
+        }
+
+        fn decompress(&self, _: &[u8], dst: &mut [u8]) -> Result<usize> {
+            Ok(dst.len() + 1)
+        }
+    }
+
+    /// Panics when dropped.
+    struct PanickingDrop;
+
+    impl Compressor for PanickingDrop {
+        fn compress(&self, _: &[u8], _: &mut [u8]) -> Result<Option<usize>> {
+            Ok(None)
+        }
+
+        fn decompress(&self, _: &[u8], _: &mut [u8]) -> Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Drop for PanickingDrop {
+        fn drop(&mut self) {
+            panic!("cannot drop")
+        }
+    }
+
+    #[test]
+    fn test_adapter() {
+        let mut adapter = CompressorAdapter::new(Rle);
+        let mut src = b"aaaabbc".to_vec();
+        let mut compressed = vec![0u8; 14];
+        let mut decompressed = vec![0u8; 7];
+        let (mut len, mut failed) = (0usize, 0);
+        unsafe {
+            let ret = pre_size(
+                &mut adapter.base,
+                ptr::null_mut(),
+                src.as_mut_ptr(),
+                src.len(),
+                &mut len,
+            );
+            assert_eq!((ret, len), (0, 14));
+            let ret = compress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                src.as_mut_ptr(),
+                src.len(),
+                compressed.as_mut_ptr(),
+                compressed.len(),
+                &mut len,
+                &mut failed,
+            );
+            assert_eq!((ret, failed), (0, 0));
+            assert_eq!(&compressed[..len], b"\x04a\x02b\x01c");
+            let ret = decompress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                compressed.as_mut_ptr(),
+                len,
+                decompressed.as_mut_ptr(),
+                decompressed.len(),
+                &mut len,
+            );
+            assert_eq!(ret, 0);
+            assert_eq!(&decompressed[..len], &src[..]);
+        }
+
+        let mut adapter = CompressorAdapter::new(Panicking);
+        unsafe {
+            let ret = compress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                src.as_mut_ptr(),
+                src.len(),
+                compressed.as_mut_ptr(),
+                compressed.len(),
+                &mut len,
+                &mut failed,
+            );
+            assert_eq!(ret, WT_ERROR);
+            let ret = decompress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                compressed.as_mut_ptr(),
+                compressed.len(),
+                decompressed.as_mut_ptr(),
+                decompressed.len(),
+                &mut len,
+            );
+            assert_eq!(ret, EINVAL);
+        }
+
+        let mut adapter = CompressorAdapter::new(Lying);
+        len = 0;
+        unsafe {
+            let ret = compress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                src.as_mut_ptr(),
+                src.len(),
+                compressed.as_mut_ptr(),
+                compressed.len(),
+                &mut len,
+                &mut failed,
+            );
+            assert_eq!((ret, len), (EINVAL, 0));
+            let ret = decompress(
+                &mut adapter.base,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                decompressed.as_mut_ptr(),
+                decompressed.len(),
+                &mut len,
+            );
+            assert_eq!((ret, len), (EINVAL, 0));
+        }
+
+        let adapter = Box::into_raw(CompressorAdapter::new(PanickingDrop));
+        unsafe {
+            let ret = terminate(adapter as *mut WT_COMPRESSOR, ptr::null_mut());
+            assert_eq!(ret, WT_ERROR);
+        }
+    }
+
+    #[test]
+    fn test_compressor() {
+        let home = "target/wt_compressor";
+        test_utils::make_work_dir(home, true);
+        let value = "a".repeat(1000);
+        {
+            let conn = Connection::open(home, "create").unwrap();
+            conn.add_compressor("rle", Rle).unwrap();
+            let session = conn.open_session("").unwrap();
+            session
+                .create(
+                    "table:compressor",
+                    "key_format=i,value_format=S,block_compressor=rle",
+                )
+                .unwrap();
+            let mut cursor = session.open_cursor("table:compressor", "").unwrap();
+            for key in 0..100 {
+                cursor.set_key(&key).unwrap();
+                cursor.set_value(&value).unwrap();
+                cursor.insert().unwrap();
+            }
+        }
+
+        let conn = Connection::open(home, "").unwrap();
+        conn.add_compressor("rle", Rle).unwrap();
+        let session = conn.open_session("").unwrap();
+        let mut cursor = session.open_cursor("table:compressor", "").unwrap();
+        let records = cursor
+            .iter::<i32, String>()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 100);
+        assert!(records.iter().all(|(_, v)| *v == value));
+    }
+}
@@ -0,0 +1,1174 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builders of configuration strings.
+//!
+//! The builders render to configuration strings with `to_string()`, which can
+//! be passed to the methods taking a configuration:
+//!
+//! ```no_run
+//! use wiredtiger::config::{ConnectionConfig, TableConfig};
+//! use wiredtiger::Connection;
+//!
+//! let config = ConnectionConfig::new().create(true).cache_size(1 << 30);
+//! let conn = Connection::open("db", config.to_string()).unwrap();
+//! let session = conn.open_session("").unwrap();
+//! let table = TableConfig::new().key_format("S").value_format("S");
+//! session.create("table:t", table.to_string()).unwrap();
+//! ```
+
+use crate::error::{Error, Result, EINVAL};
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::fmt::{Display, Formatter};
+use std::ptr;
+use std::str::FromStr;
+use wiredtiger_sys::{
+    __wt_config_item_WT_CONFIG_ITEM_TYPE_WT_CONFIG_ITEM_BOOL as ITEM_BOOL,
+    __wt_config_item_WT_CONFIG_ITEM_TYPE_WT_CONFIG_ITEM_ID as ITEM_ID,
+    __wt_config_item_WT_CONFIG_ITEM_TYPE_WT_CONFIG_ITEM_NUM as ITEM_NUM,
+    __wt_config_item_WT_CONFIG_ITEM_TYPE_WT_CONFIG_ITEM_STRING as ITEM_STRING,
+    __wt_config_item_WT_CONFIG_ITEM_TYPE_WT_CONFIG_ITEM_STRUCT as ITEM_STRUCT,
+    wiredtiger_config_parser_open, wiredtiger_config_validate, WT_CONFIG_ITEM, WT_CONFIG_PARSER,
+    WT_NOTFOUND,
+};
+
+/// Quote `s` as a configuration string value, escaping `"` and `\\` with a
+/// backslash.
+pub(crate) fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Render `items` as a configuration list, e.g. `(a,b)`.
+pub(crate) fn list<I: IntoIterator<Item = T>, T: Display>(items: I) -> String {
+    let items: Vec<_> = items.into_iter().map(|item| item.to_string()).collect();
+    format!("({})", items.join(","))
+}
+
+/// Check `config` against the configuration of the WiredTiger method `method`,
+/// e.g. `WT_SESSION.create`.
+pub fn validate(method: &str, config: &str) -> Result<()> {
+    let c_method = CString::new(method.as_bytes()).unwrap();
+    let c_config = CString::new(config.as_bytes()).unwrap();
+    unsafe {
+        wt_try!(wiredtiger_config_validate(
+            ptr::null_mut(),
+            ptr::null_mut(),
+            c_method.as_ptr(),
+            c_config.as_ptr()
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Value(String),
+    Nested(ConfigMap),
+}
+
+/// Ordered configuration items, where setting a key again replaces its value.
+#[derive(Debug, Clone, Default)]
+struct ConfigMap {
+    items: Vec<(String, Item)>,
+}
+
+impl ConfigMap {
+    fn set_item(&mut self, key: &str, item: Item) {
+        match self.items.iter_mut().find(|(k, _)| k == key) {
+            Some((_, old)) => *old = item,
+            None => self.items.push((key.to_string(), item)),
+        }
+    }
+
+    fn set<V: Display>(&mut self, key: &str, value: V) {
+        self.set_item(key, Item::Value(value.to_string()));
+    }
+
+    /// The nested configuration of `key`, e.g. `log` in `log=(enabled=true)`.
+    fn nested(&mut self, key: &str) -> &mut ConfigMap {
+        let index = match self.items.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+                self.items
+                    .push((key.to_string(), Item::Nested(ConfigMap::default())));
+                self.items.len() - 1
+            }
+        };
+        if let Item::Value(_) = self.items[index].1 {
+            self.items[index].1 = Item::Nested(ConfigMap::default());
+        }
+        match &mut self.items[index].1 {
+            Item::Nested(map) => map,
+            Item::Value(_) => unreachable!(),
+        }
+    }
+}
+
+impl Display for ConfigMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, item)) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match item {
+                Item::Value(value) => write!(f, "{}={}", key, value)?,
+                Item::Nested(map) => write!(f, "{}=({})", key, map)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! config_builder {
+    ($name: ident, $method: expr) => {
+        impl $name {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Set an option this builder has no method for. `value` is
+            /// written as is.
+            pub fn option<V: Display>(mut self, key: &str, value: V) -> Self {
+                self.0.set(key, value);
+                self
+            }
+
+            /// Check the configuration with WiredTiger.
+            pub fn validate(&self) -> Result<()> {
+                validate($method, &self.to_string())
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+/// Statistics to maintain, see the `statistics` configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Statistics {
+    All,
+    CacheWalk,
+    Fast,
+    None,
+    Clear,
+    TreeWalk,
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Statistics::All => "all",
+            Statistics::CacheWalk => "cache_walk",
+            Statistics::Fast => "fast",
+            Statistics::None => "none",
+            Statistics::Clear => "clear",
+            Statistics::TreeWalk => "tree_walk",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The isolation level of transactions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Isolation {
+    ReadUncommitted,
+    ReadCommitted,
+    Snapshot,
+}
+
+impl Display for Isolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Isolation::ReadUncommitted => "read-uncommitted",
+            Isolation::ReadCommitted => "read-committed",
+            Isolation::Snapshot => "snapshot",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How the key of a join cursor's reference cursor compares to the rows
+/// matching the join condition.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JoinCompare {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+}
+
+impl Display for JoinCompare {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            JoinCompare::Eq => "eq",
+            JoinCompare::Ge => "ge",
+            JoinCompare::Gt => "gt",
+            JoinCompare::Le => "le",
+            JoinCompare::Lt => "lt",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How the conditions joined on the same index are combined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JoinOperation {
+    And,
+    Or,
+}
+
+impl Display for JoinOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            JoinOperation::And => "and",
+            JoinOperation::Or => "or",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Configuration of `Connection::open`.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionConfig(ConfigMap);
+
+config_builder!(ConnectionConfig, "wiredtiger_open");
+
+impl ConnectionConfig {
+    /// Create the database if it does not exist.
+    pub fn create(mut self, create: bool) -> Self {
+        self.0.set("create", create);
+        self
+    }
+
+    /// Fail if the database already exists.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.0.set("exclusive", exclusive);
+        self
+    }
+
+    /// Size of the cache in bytes.
+    pub fn cache_size(mut self, bytes: u64) -> Self {
+        self.0.set("cache_size", bytes);
+        self
+    }
+
+    /// Maximum number of sessions.
+    pub fn session_max(mut self, session_max: u32) -> Self {
+        self.0.set("session_max", session_max);
+        self
+    }
+
+    /// Enable write-ahead logging.
+    pub fn log(mut self, enabled: bool) -> Self {
+        self.0.nested("log").set("enabled", enabled);
+        self
+    }
+
+    /// Directory of the log files, relative to the database home.
+    pub fn log_path(mut self, path: &str) -> Self {
+        self.0.nested("log").set("path", quote(path));
+        self
+    }
+
+    /// Maximum size of a log file in bytes.
+    pub fn log_file_max(mut self, bytes: u64) -> Self {
+        self.0.nested("log").set("file_max", bytes);
+        self
+    }
+
+    /// Statistics to maintain.
+    pub fn statistics(mut self, statistics: &[Statistics]) -> Self {
+        self.0.set("statistics", list(statistics));
+        self
+    }
+
+    /// Write statistics to log files every `seconds`.
+    pub fn statistics_log_wait(mut self, seconds: u32) -> Self {
+        self.0.nested("statistics_log").set("wait", seconds);
+        self
+    }
+
+    /// Checkpoint the database every `seconds`.
+    pub fn checkpoint_wait(mut self, seconds: u32) -> Self {
+        self.0.nested("checkpoint").set("wait", seconds);
+        self
+    }
+
+    /// Shared libraries of extensions to load.
+    pub fn extensions<S: AsRef<str>>(mut self, paths: &[S]) -> Self {
+        self.0
+            .set("extensions", list(paths.iter().map(|p| quote(p.as_ref()))));
+        self
+    }
+
+    /// Prefix of error messages.
+    pub fn error_prefix(mut self, prefix: &str) -> Self {
+        self.0.set("error_prefix", quote(prefix));
+        self
+    }
+
+    /// Whether to read the `WiredTiger.basecfg` file.
+    pub fn config_base(mut self, config_base: bool) -> Self {
+        self.0.set("config_base", config_base);
+        self
+    }
+}
+
+/// Configuration of `Connection::open_session`.
+#[derive(Debug, Clone, Default)]
+pub struct SessionConfig(ConfigMap);
+
+config_builder!(SessionConfig, "WT_CONNECTION.open_session");
+
+impl SessionConfig {
+    /// Default isolation level of the session's transactions.
+    pub fn isolation(mut self, isolation: Isolation) -> Self {
+        self.0.set("isolation", isolation);
+        self
+    }
+
+    /// Cache closed cursors for reuse.
+    pub fn cache_cursors(mut self, cache_cursors: bool) -> Self {
+        self.0.set("cache_cursors", cache_cursors);
+        self
+    }
+
+    /// Let the session's operations ignore the cache size limit.
+    pub fn ignore_cache_size(mut self, ignore_cache_size: bool) -> Self {
+        self.0.set("ignore_cache_size", ignore_cache_size);
+        self
+    }
+}
+
+/// Configuration of `Session::create`.
+#[derive(Debug, Clone, Default)]
+pub struct TableConfig(ConfigMap);
+
+config_builder!(TableConfig, "WT_SESSION.create");
+
+impl TableConfig {
+    /// Format of keys, e.g. `S` or `r` for record numbers.
+    pub fn key_format(mut self, format: &str) -> Self {
+        self.0.set("key_format", format);
+        self
+    }
+
+    /// Format of values, e.g. `S` or `SiQ`.
+    pub fn value_format(mut self, format: &str) -> Self {
+        self.0.set("value_format", format);
+        self
+    }
+
+    /// Names of the key columns followed by the value columns.
+    pub fn columns<S: AsRef<str>>(mut self, columns: &[S]) -> Self {
+        self.0
+            .set("columns", list(columns.iter().map(|c| c.as_ref())));
+        self
+    }
+
+    /// Names of the column groups of the table.
+    pub fn colgroups<S: AsRef<str>>(mut self, colgroups: &[S]) -> Self {
+        self.0
+            .set("colgroups", list(colgroups.iter().map(|c| c.as_ref())));
+        self
+    }
+
+    /// Name of the compressor of blocks, e.g. `snappy`.
+    pub fn block_compressor(mut self, compressor: &str) -> Self {
+        self.0.set("block_compressor", quote(compressor));
+        self
+    }
+
+    /// Name of the collator ordering keys.
+    pub fn collator(mut self, collator: &str) -> Self {
+        self.0.set("collator", quote(collator));
+        self
+    }
+
+    /// Log updates to the table, if logging is enabled on the connection.
+    pub fn log(mut self, enabled: bool) -> Self {
+        self.0.nested("log").set("enabled", enabled);
+        self
+    }
+
+    /// Maximum size of leaf pages in bytes.
+    pub fn leaf_page_max(mut self, bytes: u64) -> Self {
+        self.0.set("leaf_page_max", bytes);
+        self
+    }
+
+    /// Maximum size of internal pages in bytes.
+    pub fn internal_page_max(mut self, bytes: u64) -> Self {
+        self.0.set("internal_page_max", bytes);
+        self
+    }
+
+    /// Maximum size of pages held in memory in bytes.
+    pub fn memory_page_max(mut self, bytes: u64) -> Self {
+        self.0.set("memory_page_max", bytes);
+        self
+    }
+
+    /// Fail if the object already exists.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.0.set("exclusive", exclusive);
+        self
+    }
+
+    /// Application-owned metadata stored with the object.
+    pub fn app_metadata(mut self, metadata: &str) -> Self {
+        self.0.set("app_metadata", quote(metadata));
+        self
+    }
+}
+
+/// Configuration of `Session::create_colgroup`.
+#[derive(Debug, Clone, Default)]
+pub struct ColgroupConfig(ConfigMap);
+
+config_builder!(ColgroupConfig, "WT_SESSION.create");
+
+impl ColgroupConfig {
+    /// Names of the value columns stored in the column group.
+    pub fn columns<S: AsRef<str>>(mut self, columns: &[S]) -> Self {
+        self.0
+            .set("columns", list(columns.iter().map(|c| c.as_ref())));
+        self
+    }
+
+    /// Name of the compressor of blocks, e.g. `snappy`.
+    pub fn block_compressor(mut self, compressor: &str) -> Self {
+        self.0.set("block_compressor", quote(compressor));
+        self
+    }
+
+    /// Maximum size of leaf pages in bytes.
+    pub fn leaf_page_max(mut self, bytes: u64) -> Self {
+        self.0.set("leaf_page_max", bytes);
+        self
+    }
+
+    /// Maximum size of pages held in memory in bytes.
+    pub fn memory_page_max(mut self, bytes: u64) -> Self {
+        self.0.set("memory_page_max", bytes);
+        self
+    }
+
+    /// Fail if the object already exists.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.0.set("exclusive", exclusive);
+        self
+    }
+}
+
+/// Configuration of `Session::create_index`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexConfig(ConfigMap);
+
+config_builder!(IndexConfig, "WT_SESSION.create");
+
+impl IndexConfig {
+    /// Names of the table columns the index is keyed on.
+    pub fn columns<S: AsRef<str>>(mut self, columns: &[S]) -> Self {
+        self.0
+            .set("columns", list(columns.iter().map(|c| c.as_ref())));
+        self
+    }
+
+    /// Name of the collator ordering index keys.
+    pub fn collator(mut self, collator: &str) -> Self {
+        self.0.set("collator", quote(collator));
+        self
+    }
+
+    /// Name of the extractor generating index keys from records.
+    pub fn extractor(mut self, extractor: &str) -> Self {
+        self.0.set("extractor", quote(extractor));
+        self
+    }
+
+    /// Format of keys generated by the extractor.
+    pub fn key_format(mut self, format: &str) -> Self {
+        self.0.set("key_format", format);
+        self
+    }
+
+    /// Index keys of a record never change, so updates can skip the index.
+    pub fn immutable(mut self, immutable: bool) -> Self {
+        self.0.set("immutable", immutable);
+        self
+    }
+
+    /// Fail if the object already exists.
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.0.set("exclusive", exclusive);
+        self
+    }
+}
+
+/// Configuration of `Session::join`.
+#[derive(Debug, Clone, Default)]
+pub struct JoinConfig(ConfigMap);
+
+config_builder!(JoinConfig, "WT_SESSION.join");
+
+impl JoinConfig {
+    /// Compare the rows to the key of the reference cursor, `eq` by default.
+    pub fn compare(mut self, compare: JoinCompare) -> Self {
+        self.0.set("compare", compare);
+        self
+    }
+
+    /// Combine the conditions on the same index with `and` (the default) or
+    /// `or`.
+    pub fn operation(mut self, operation: JoinOperation) -> Self {
+        self.0.set("operation", operation);
+        self
+    }
+
+    /// Check the condition with a bloom filter sized for `count` entries
+    /// instead of walking the index.
+    pub fn bloom(mut self, count: u64) -> Self {
+        self.0.set("strategy", "bloom");
+        self.0.set("count", count);
+        self
+    }
+
+    /// Number of bits per item of the bloom filter.
+    pub fn bloom_bit_count(mut self, bits: u32) -> Self {
+        self.0.set("bloom_bit_count", bits);
+        self
+    }
+
+    /// Number of hash values per item of the bloom filter.
+    pub fn bloom_hash_count(mut self, hashes: u32) -> Self {
+        self.0.set("bloom_hash_count", hashes);
+        self
+    }
+
+    /// Return the false positives of the bloom filter instead of checking
+    /// them.
+    pub fn bloom_false_positives(mut self, enabled: bool) -> Self {
+        self.0.set("bloom_false_positives", enabled);
+        self
+    }
+}
+
+/// Configuration of `Session::open_backup`.
+#[derive(Debug, Clone, Default)]
+pub struct BackupConfig(ConfigMap);
+
+config_builder!(BackupConfig, "WT_SESSION.open_cursor");
+
+impl BackupConfig {
+    /// Track the blocks modified after this backup under `id`, so a later
+    /// backup can copy only them.
+    pub fn incremental_id(mut self, id: &str) -> Self {
+        let incremental = self.0.nested("incremental");
+        incremental.set("enabled", true);
+        incremental.set("this_id", quote(id));
+        self
+    }
+
+    /// Only list the blocks modified since the backup `id`. Requires
+    /// `incremental_id` too.
+    pub fn incremental_source(mut self, id: &str) -> Self {
+        let incremental = self.0.nested("incremental");
+        incremental.set("enabled", true);
+        incremental.set("src_id", quote(id));
+        self
+    }
+
+    /// Size in bytes of the blocks tracked for incremental backups.
+    pub fn incremental_granularity(mut self, bytes: u64) -> Self {
+        self.0.nested("incremental").set("granularity", bytes);
+        self
+    }
+
+    /// Stop tracking modified blocks and forget all backup IDs.
+    pub fn force_stop(mut self, force_stop: bool) -> Self {
+        self.0.nested("incremental").set("force_stop", force_stop);
+        self
+    }
+}
+
+/// Configuration of `Session::open_cursor`.
+#[derive(Debug, Clone, Default)]
+pub struct CursorConfig(ConfigMap);
+
+config_builder!(CursorConfig, "WT_SESSION.open_cursor");
+
+impl CursorConfig {
+    /// Whether inserts overwrite existing records, and updates and removes
+    /// succeed for missing keys. WiredTiger does by default.
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.0.set("overwrite", overwrite);
+        self
+    }
+
+    /// Pass keys and values in their packed form.
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.0.set("raw", raw);
+        self
+    }
+
+    /// Only allow reading through the cursor.
+    pub fn readonly(mut self, readonly: bool) -> Self {
+        self.0.set("readonly", readonly);
+        self
+    }
+
+    /// Allocate record numbers for new records of column stores.
+    pub fn append(mut self, append: bool) -> Self {
+        self.0.set("append", append);
+        self
+    }
+
+    /// Bulk-load a newly created object.
+    pub fn bulk(mut self, bulk: bool) -> Self {
+        self.0.set("bulk", bulk);
+        self
+    }
+
+    /// Read the checkpoint `name` instead of the live data.
+    pub fn checkpoint(mut self, name: &str) -> Self {
+        self.0.set("checkpoint", quote(name));
+        self
+    }
+
+    /// Statistics returned by statistics cursors.
+    pub fn statistics(mut self, statistics: &[Statistics]) -> Self {
+        self.0.set("statistics", list(statistics));
+        self
+    }
+}
+
+/// Whether `s` must be quoted to be read back as a single identifier.
+fn needs_quote(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            !chars.all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c))
+        }
+        _ => true,
+    }
+}
+
+/// A value in a configuration string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    /// An unquoted identifier, e.g. `snappy`.
+    Id(String),
+    /// A quoted string.
+    String(String),
+    /// A list of values, e.g. `(fast,clear)`.
+    List(Vec<ConfigValue>),
+    /// A nested configuration, e.g. `(enabled=true,path="journal")`.
+    Struct(Config),
+}
+
+impl ConfigValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConfigValue::Bool(b) => Some(*b),
+            ConfigValue::Int(i) => Some(*i != 0),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            ConfigValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// The text of an identifier or a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ConfigValue::Id(s) | ConfigValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[ConfigValue]> {
+        match self {
+            ConfigValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_struct(&self) -> Option<&Config> {
+        match self {
+            ConfigValue::Struct(config) => Some(config),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ConfigValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValue::Bool(b) => write!(f, "{}", b),
+            ConfigValue::Int(i) => write!(f, "{}", i),
+            ConfigValue::Id(s) if !needs_quote(s) => write!(f, "{}", s),
+            ConfigValue::Id(s) | ConfigValue::String(s) => write!(f, "{}", quote(s)),
+            ConfigValue::List(list) => write!(f, "{}", self::list(list)),
+            ConfigValue::Struct(config) => write!(f, "({})", config),
+        }
+    }
+}
+
+/// A parsed configuration string.
+///
+/// It keeps the order of the keys, and renders back to a configuration string
+/// with `to_string()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    items: Vec<(String, ConfigValue)>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the value of `key`. Nested keys are separated by dots, e.g.
+    /// `log.enabled`.
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        let mut keys = key.split('.');
+        let first = keys.next()?;
+        let mut value = self.items.iter().find(|(k, _)| k == first).map(|(_, v)| v);
+        for key in keys {
+            value = value?.as_struct()?.get(key);
+        }
+        value
+    }
+
+    /// Set the value of `key`, replacing its value if any.
+    pub fn set<K: Into<String>>(&mut self, key: K, value: ConfigValue) {
+        let key = key.into();
+        match self.items.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => *old = value,
+            None => self.items.push((key, value)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<ConfigValue> {
+        let index = self.items.iter().position(|(k, _)| k == key)?;
+        Some(self.items.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ConfigValue)> {
+        self.items.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Apply `overrides` on top of this configuration. Nested configurations
+    /// are merged key by key, other values are replaced.
+    pub fn merge(&mut self, overrides: &Config) {
+        for (key, value) in &overrides.items {
+            let existing = self.items.iter_mut().find(|(k, _)| k == key);
+            match (existing, value) {
+                (Some((_, ConfigValue::Struct(base))), ConfigValue::Struct(nested)) => {
+                    base.merge(nested)
+                }
+                _ => self.set(key.clone(), value.clone()),
+            }
+        }
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let items = ConfigParser::new(s)?.collect::<Result<_>>()?;
+        Ok(Config { items })
+    }
+}
+
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            if needs_quote(key) {
+                write!(f, "{}={}", quote(key), value)?;
+            } else {
+                write!(f, "{}={}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a configuration string with WiredTiger's parser, yielding its keys
+/// and values in order.
+pub struct ConfigParser {
+    inner: *mut WT_CONFIG_PARSER,
+    // The parser refers to the string until it is closed.
+    _config: CString,
+}
+
+impl ConfigParser {
+    pub fn new(config: &str) -> Result<Self> {
+        let c_config = CString::new(config.as_bytes()).unwrap();
+        let mut parser: *mut WT_CONFIG_PARSER = ptr::null_mut();
+        unsafe {
+            wt_try!(wiredtiger_config_parser_open(
+                ptr::null_mut(),
+                c_config.as_ptr(),
+                config.len(),
+                &mut parser as *mut *mut WT_CONFIG_PARSER
+            ));
+        }
+        assert!(!parser.is_null());
+        Ok(Self {
+            inner: parser,
+            _config: c_config,
+        })
+    }
+
+    /// Get the value of `key`. Nested keys are separated by dots, e.g.
+    /// `log.enabled`.
+    pub fn get(&self, key: &str) -> Result<Option<ConfigValue>> {
+        let c_key = CString::new(key.as_bytes()).unwrap();
+        let mut value = empty_item();
+        unsafe {
+            let get = (*self.inner).get.expect("null function pointer");
+            match get(
+                self.inner,
+                c_key.as_ptr(),
+                &mut value as *mut WT_CONFIG_ITEM,
+            ) {
+                0 => item_to_value(&value).map(Some),
+                WT_NOTFOUND => Ok(None),
+                errcode => Err(Error::from(errcode)),
+            }
+        }
+    }
+
+    /// The next raw key and value, or `None` at the end.
+    fn next_item(&mut self) -> Result<Option<(WT_CONFIG_ITEM, WT_CONFIG_ITEM)>> {
+        let mut key = empty_item();
+        let mut value = empty_item();
+        unsafe {
+            let next = (*self.inner).next.expect("null function pointer");
+            match next(
+                self.inner,
+                &mut key as *mut WT_CONFIG_ITEM,
+                &mut value as *mut WT_CONFIG_ITEM,
+            ) {
+                0 => Ok(Some((key, value))),
+                WT_NOTFOUND => Ok(None),
+                errcode => Err(Error::from(errcode)),
+            }
+        }
+    }
+}
+
+impl Iterator for ConfigParser {
+    type Item = Result<(String, ConfigValue)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = match self.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => return None,
+            Err(error) => return Some(Err(error)),
+        };
+        let pair =
+            unsafe { item_str(&key).and_then(|k| Ok((k.to_string(), item_to_value(&value)?))) };
+        Some(pair)
+    }
+}
+
+impl Drop for ConfigParser {
+    fn drop(&mut self) {
+        unsafe {
+            let close = (*self.inner).close.expect("null function pointer");
+            close(self.inner);
+        }
+    }
+}
+
+fn empty_item() -> WT_CONFIG_ITEM {
+    unsafe { std::mem::zeroed() }
+}
+
+/// The text of a configuration item, without quotes and the escapes added by
+/// `quote`.
+unsafe fn item_str<'a>(item: &WT_CONFIG_ITEM) -> Result<Cow<'a, str>> {
+    if item.len == 0 {
+        return Ok(Cow::Borrowed(""));
+    }
+    let bytes = std::slice::from_raw_parts(item.str_ as *const u8, item.len);
+    let s = std::str::from_utf8(bytes).map_err(|_| Error::System(EINVAL))?;
+    let quoted = s.len() >= 2 && s.starts_with('"') && s.ends_with('"');
+    let s = if quoted { &s[1..s.len() - 1] } else { s };
+    if (quoted || item.type_ == ITEM_STRING) && s.contains('\\') {
+        Ok(Cow::Owned(unescape(s)))
+    } else {
+        Ok(Cow::Borrowed(s))
+    }
+}
+
+/// Remove the backslashes escaping the characters of a quoted string.
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Whether the value is the one WiredTiger reports for a key without value.
+fn is_key_only(value: &WT_CONFIG_ITEM) -> bool {
+    value.type_ == ITEM_NUM && value.len == 0
+}
+
+unsafe fn item_to_value(item: &WT_CONFIG_ITEM) -> Result<ConfigValue> {
+    let text = item_str(item)?;
+    let s: &str = &text;
+    Ok(match item.type_ {
+        ITEM_STRING => ConfigValue::String(s.to_string()),
+        ITEM_BOOL => ConfigValue::Bool(item.val != 0),
+        ITEM_ID => ConfigValue::Id(s.to_string()),
+        ITEM_NUM if s.is_empty() => ConfigValue::Bool(item.val != 0),
+        ITEM_NUM => ConfigValue::Int(item.val),
+        ITEM_STRUCT => {
+            let inner = if s.starts_with('(') || s.starts_with('[') {
+                &s[1..s.len() - 1]
+            } else {
+                s
+            };
+            parse_nested(inner)?
+        }
+        _ => return Err(Error::System(EINVAL)),
+    })
+}
+
+/// Parse the contents of a nested value, which is a list if none of its keys
+/// has a value.
+fn parse_nested(config: &str) -> Result<ConfigValue> {
+    let mut parser = ConfigParser::new(config)?;
+    let mut list = Vec::new();
+    let mut items = Vec::new();
+    let mut is_list = true;
+    while let Some((key, value)) = parser.next_item()? {
+        unsafe {
+            if is_key_only(&value) {
+                list.push(item_to_value(&key)?);
+            } else {
+                is_list = false;
+            }
+            items.push((item_str(&key)?.to_string(), item_to_value(&value)?));
+        }
+    }
+    Ok(if is_list {
+        ConfigValue::List(list)
+    } else {
+        ConfigValue::Struct(Config { items })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_config() {
+        let config = ConnectionConfig::new()
+            .create(true)
+            .cache_size(1 << 20)
+            .log(true)
+            .log_path("journal")
+            .statistics(&[Statistics::Fast, Statistics::Clear])
+            .create(false);
+        assert_eq!(
+            config.to_string(),
+            "create=false,cache_size=1048576,log=(enabled=true,path=\"journal\"),\
+             statistics=(fast,clear)"
+        );
+        config.validate().unwrap();
+        assert!(ConnectionConfig::new()
+            .option("no_such_option", 1)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_table_config() {
+        let config = TableConfig::new()
+            .key_format("S")
+            .value_format("Si")
+            .columns(&["id", "name", "age"])
+            .block_compressor("snappy");
+        assert_eq!(
+            config.to_string(),
+            "key_format=S,value_format=Si,columns=(id,name,age),block_compressor=\"snappy\""
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_quote() {
+        let config = TableConfig::new()
+            .key_format("S")
+            .value_format("S")
+            .app_metadata("x\",log=(enabled=false),a=\"\\");
+        assert_eq!(
+            config.to_string(),
+            "key_format=S,value_format=S,app_metadata=\"x\\\",log=(enabled=false),a=\\\"\\\\\""
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_colgroup_config() {
+        let config = ColgroupConfig::new()
+            .columns(&["payload"])
+            .block_compressor("snappy");
+        assert_eq!(
+            config.to_string(),
+            "columns=(payload),block_compressor=\"snappy\""
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_index_config() {
+        let config = IndexConfig::new().columns(&["name", "age"]).immutable(true);
+        assert_eq!(config.to_string(), "columns=(name,age),immutable=true");
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_join_config() {
+        let config = JoinConfig::new()
+            .compare(JoinCompare::Ge)
+            .bloom(1000)
+            .operation(JoinOperation::Or);
+        assert_eq!(
+            config.to_string(),
+            "compare=ge,strategy=bloom,count=1000,operation=or"
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_backup_config() {
+        let config = BackupConfig::new()
+            .incremental_source("ID1")
+            .incremental_id("ID2");
+        assert_eq!(
+            config.to_string(),
+            "incremental=(enabled=true,src_id=\"ID1\",this_id=\"ID2\")"
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_session_cursor_config() {
+        let config = SessionConfig::new().isolation(Isolation::Snapshot);
+        assert_eq!(config.to_string(), "isolation=snapshot");
+        config.validate().unwrap();
+
+        let config = CursorConfig::new().overwrite(false).raw(true);
+        assert_eq!(config.to_string(), "overwrite=false,raw=true");
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_config_parser() {
+        let config = "create,cache_size=1GB,log=(enabled=true,path=\"journal\"),\
+                      statistics=(fast,clear),extensions=[\"a.so\"],block_compressor=snappy";
+        let parsed: Config = config.parse().unwrap();
+        assert_eq!(parsed.get("create"), Some(&ConfigValue::Bool(true)));
+        assert_eq!(parsed.get("cache_size"), Some(&ConfigValue::Int(1 << 30)));
+        assert_eq!(parsed.get("log.enabled"), Some(&ConfigValue::Bool(true)));
+        assert_eq!(
+            parsed.get("log.path").and_then(ConfigValue::as_str),
+            Some("journal")
+        );
+        assert_eq!(
+            parsed.get("statistics"),
+            Some(&ConfigValue::List(vec![
+                ConfigValue::Id("fast".to_string()),
+                ConfigValue::Id("clear".to_string()),
+            ]))
+        );
+        assert_eq!(
+            parsed.get("block_compressor"),
+            Some(&ConfigValue::Id("snappy".to_string()))
+        );
+
+        let rendered = parsed.to_string();
+        assert_eq!(rendered.parse::<Config>().unwrap(), parsed);
+
+        let escaped = ConfigValue::String("say \"hi\" \\ bye".to_string());
+        let metadata: Config = format!("app_metadata={}", escaped).parse().unwrap();
+        assert_eq!(metadata.get("app_metadata"), Some(&escaped));
+        assert_eq!(metadata.to_string().parse::<Config>().unwrap(), metadata);
+
+        let parser = ConfigParser::new(config).unwrap();
+        assert_eq!(
+            parser.get("log.enabled").unwrap(),
+            Some(ConfigValue::Bool(true))
+        );
+        assert_eq!(parser.get("no_such_key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_config_merge() {
+        let mut config: Config = "create,log=(enabled=true,file_max=1MB)".parse().unwrap();
+        let overrides: Config = "log=(enabled=false),cache_size=100".parse().unwrap();
+        config.merge(&overrides);
+        assert_eq!(
+            config.to_string(),
+            "create=true,log=(enabled=false,file_max=1048576),cache_size=100"
+        );
+    }
+}
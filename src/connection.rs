@@ -14,15 +14,37 @@
 
 //! A connection to a WiredTiger database.
 
-use crate::error::Result;
+use crate::collator::{Collator, CollatorAdapter};
+use crate::compressor::{Compressor, CompressorAdapter};
+use crate::error::{Error, Result, EBUSY};
+use crate::metadata::Schema;
+#[cfg(feature = "metrics")]
+use crate::metrics::{MetricsExporter, MetricsSampler};
 use crate::session::Session;
+use crate::statistics::{ConnectionStat, Statistics};
+use crate::timestamp::{GlobalTimestamp, GlobalTimestampQuery, Timestamp, HEX_TIMESTAMP_SIZE};
 use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
 use std::path::Path;
 use std::ptr;
-use wiredtiger_sys::{wiredtiger_open, WT_CONNECTION, WT_SESSION};
+#[cfg(feature = "metrics")]
+use std::time::Duration;
+use wiredtiger_sys::{wiredtiger_open, WT_COLLATOR, WT_COMPRESSOR, WT_CONNECTION, WT_SESSION};
+
+/// The outcome of `Connection::rollback_to_stable`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RollbackToStable {
+    /// The database was rolled back to the stable timestamp.
+    Completed,
+    /// The rollback could not run because there are active transactions or
+    /// open cursors.
+    Busy,
+}
 
 pub struct Connection {
     inner: Option<*mut WT_CONNECTION>,
+    #[cfg(feature = "metrics")]
+    sampler: Option<MetricsSampler>,
 }
 
 unsafe impl Send for Connection {}
@@ -61,14 +83,20 @@ impl Connection {
     }
 
     #[inline]
-    unsafe fn new_unchecked(conn: *mut WT_CONNECTION) -> Self {
+    pub(crate) unsafe fn new_unchecked(conn: *mut WT_CONNECTION) -> Self {
         debug_assert!(!conn.is_null());
-        Self { inner: Some(conn) }
+        Self {
+            inner: Some(conn),
+            #[cfg(feature = "metrics")]
+            sampler: None,
+        }
     }
 
     pub fn close<T: AsRef<str>>(&mut self, config: T) -> Result<()> {
         if let Some(conn) = self.inner {
             debug_assert!(!conn.is_null());
+            #[cfg(feature = "metrics")]
+            self.stop_metrics_sampler();
             unsafe {
                 let close = (*conn).close.unwrap();
 
@@ -116,6 +144,121 @@ impl Connection {
             Ok(Session::new_unchecked(session))
         }
     }
+
+    /// Register `collator` under `name`, for the `collator` configuration of
+    /// tables and indices.
+    pub fn add_collator<C: Collator>(&self, name: &str, collator: C) -> Result<()> {
+        let (conn, add_collator) = conn_api!(self, add_collator);
+        let c_name = CString::new(name.as_bytes()).unwrap();
+        let adapter = Box::into_raw(CollatorAdapter::new(collator));
+        unsafe {
+            match add_collator(
+                conn,
+                c_name.as_ptr(),
+                adapter as *mut WT_COLLATOR,
+                ptr::null(),
+            ) {
+                0 => Ok(()),
+                errcode => {
+                    drop(Box::from_raw(adapter));
+                    Err(Error::from(errcode))
+                }
+            }
+        }
+    }
+
+    /// Register `compressor` under `name`, for the `block_compressor`
+    /// configuration of tables.
+    pub fn add_compressor<C: Compressor>(&self, name: &str, compressor: C) -> Result<()> {
+        let (conn, add_compressor) = conn_api!(self, add_compressor);
+        let c_name = CString::new(name.as_bytes()).unwrap();
+        let adapter = Box::into_raw(CompressorAdapter::new(compressor));
+        unsafe {
+            match add_compressor(
+                conn,
+                c_name.as_ptr(),
+                adapter as *mut WT_COMPRESSOR,
+                ptr::null(),
+            ) {
+                0 => Ok(()),
+                errcode => {
+                    drop(Box::from_raw(adapter));
+                    Err(Error::from(errcode))
+                }
+            }
+        }
+    }
+
+    /// Describe the tables of the database with their column groups and
+    /// indices, and its files.
+    pub fn schema(&self) -> Result<Schema> {
+        let session = self.open_session("")?;
+        Ok(Schema::new(session.list_objects()?))
+    }
+
+    /// Read the statistics of the connection.
+    pub fn statistics(&self) -> Result<Statistics<ConnectionStat>> {
+        let session = self.open_session("")?;
+        let mut cursor = session.open_cursor("statistics:", "")?;
+        Statistics::read(&mut cursor)
+    }
+
+    /// Export the statistics of the connection with `exporter` every
+    /// `interval` in a background thread, replacing the running one if any.
+    ///
+    /// The thread stops when the connection is closed.
+    #[cfg(feature = "metrics")]
+    pub fn start_metrics_sampler(&mut self, exporter: MetricsExporter, interval: Duration) {
+        self.stop_metrics_sampler();
+        let conn = self.inner.expect("connection is null");
+        self.sampler = Some(MetricsSampler::start(conn, exporter, interval));
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn stop_metrics_sampler(&mut self) {
+        if let Some(sampler) = self.sampler.take() {
+            sampler.stop();
+        }
+    }
+
+    /// The OpenMetrics text of the last sample of the running sampler.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<String> {
+        self.sampler.as_ref().and_then(MetricsSampler::latest)
+    }
+
+    /// Set a global timestamp of the database.
+    pub fn set_timestamp(&self, which: GlobalTimestamp, ts: Timestamp) -> Result<()> {
+        let (conn, set_timestamp) = conn_api!(self, set_timestamp);
+        let c_config = CString::new(which.config(ts)).unwrap();
+        unsafe {
+            wt_try!(set_timestamp(conn, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Query a global timestamp of the database.
+    pub fn query_timestamp(&self, which: GlobalTimestampQuery) -> Result<Timestamp> {
+        let (conn, query_timestamp) = conn_api!(self, query_timestamp);
+        let c_config = CString::new(which.config()).unwrap();
+        let mut hex = [0 as c_char; HEX_TIMESTAMP_SIZE];
+        unsafe {
+            wt_try!(query_timestamp(conn, hex.as_mut_ptr(), c_config.as_ptr()));
+        }
+        Timestamp::from_hex_buffer(&hex)
+    }
+
+    /// Roll back the database to the stable timestamp, removing the updates
+    /// made after it.
+    pub fn rollback_to_stable<C: AsRef<str>>(&self, config: C) -> Result<RollbackToStable> {
+        let (conn, rollback_to_stable) = conn_api!(self, rollback_to_stable);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        match unsafe { rollback_to_stable(conn, c_config.as_ptr()) } {
+            0 => Ok(RollbackToStable::Completed),
+            EBUSY => Ok(RollbackToStable::Busy),
+            errcode => Err(Error::from(errcode)),
+        }
+    }
 }
 
 impl Drop for Connection {
@@ -142,4 +285,63 @@ mod tests {
         assert!(conn.is_new());
         conn.close("").unwrap();
     }
+
+    #[test]
+    fn test_global_timestamp() {
+        let home = "target/wt_global_timestamp";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        conn.set_timestamp(GlobalTimestamp::Oldest, Timestamp(5))
+            .unwrap();
+        conn.set_timestamp(GlobalTimestamp::Stable, Timestamp(10))
+            .unwrap();
+        assert_eq!(
+            conn.query_timestamp(GlobalTimestampQuery::Oldest).unwrap(),
+            Timestamp(5)
+        );
+        assert_eq!(
+            conn.query_timestamp(GlobalTimestampQuery::Stable).unwrap(),
+            Timestamp(10)
+        );
+    }
+
+    #[test]
+    fn test_rollback_to_stable() {
+        use crate::timestamp::TransactionTimestamp;
+
+        let home = "target/wt_rollback_to_stable";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let mut session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=S")
+            .unwrap();
+        conn.set_timestamp(GlobalTimestamp::Stable, Timestamp(10))
+            .unwrap();
+
+        {
+            let txn = session.transaction("").unwrap();
+            txn.session()
+                .timestamp_transaction(TransactionTimestamp::Commit, Timestamp(20))
+                .unwrap();
+            let mut cursor = txn.session().open_cursor("table:test_table", "").unwrap();
+            cursor.set_key("a").unwrap();
+            cursor.set_value("1").unwrap();
+            cursor.insert().unwrap();
+            drop(cursor);
+            txn.commit("").unwrap();
+        }
+
+        session.begin_transaction("").unwrap();
+        assert_eq!(conn.rollback_to_stable("").unwrap(), RollbackToStable::Busy);
+        session.rollback_transaction("").unwrap();
+
+        assert_eq!(
+            conn.rollback_to_stable("").unwrap(),
+            RollbackToStable::Completed
+        );
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        cursor.set_key("a").unwrap();
+        assert!(!cursor.search().unwrap());
+    }
 }
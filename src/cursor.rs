@@ -14,25 +14,480 @@
 
 //! Search, iterate and modify data.
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::pack::{self, Pack, Unpack};
 use crate::Session;
+use std::cmp::Ordering;
+use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
-use wiredtiger_sys::WT_CURSOR;
+use std::ops::{Bound, RangeBounds};
+use std::os::raw::c_int;
+use std::ptr;
+use wiredtiger_sys::{WT_CURSOR, WT_CURSTD_RAW, WT_ITEM, WT_NOTFOUND, WT_SESSION};
+
+macro_rules! cursor_api {
+    ($cursor: ident, $api: ident) => {
+        unsafe {
+            let cursor = $cursor.inner.expect("cursor is null");
+            let api = (*cursor).$api.expect("null function pointer");
+            (cursor, api)
+        }
+    };
+}
 
 pub struct Cursor<'a> {
     inner: Option<*mut WT_CURSOR>,
+    // WiredTiger refers to the packed key and value until they are replaced.
+    key: Vec<u8>,
+    value: Vec<u8>,
     session: PhantomData<&'a Session<'a>>,
 }
 
+/// Where `Cursor::search_near` positioned the cursor relative to the key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SearchNear {
+    /// The cursor is on a record smaller than the key.
+    Smaller,
+    /// The cursor is on the key.
+    Exact,
+    /// The cursor is on a record larger than the key.
+    Larger,
+}
+
+/// A range limit on packed keys.
+type PackedBound = Bound<Vec<u8>>;
+
+/// Map `WT_NOTFOUND` to `Ok(false)` and success to `Ok(true)`.
+#[inline]
+fn found(errcode: i32) -> Result<bool> {
+    match errcode {
+        0 => Ok(true),
+        WT_NOTFOUND => Ok(false),
+        errcode => Err(Error::from(errcode)),
+    }
+}
+
+/// Run `f` with the cursor in raw mode, so keys and values are passed as
+/// packed `WT_ITEM`s whatever the cursor's formats are.
+unsafe fn with_raw<R, F: FnOnce() -> R>(cursor: *mut WT_CURSOR, f: F) -> R {
+    let raw = u64::from(WT_CURSTD_RAW);
+    let saved = (*cursor).flags & raw;
+    (*cursor).flags |= raw;
+    let result = f();
+    (*cursor).flags = ((*cursor).flags & !raw) | saved;
+    result
+}
+
 impl<'a> Cursor<'a> {
     pub(crate) fn new_unchecked(cursor: *mut WT_CURSOR) -> Self {
         debug_assert!(!cursor.is_null());
         Self {
             inner: Some(cursor),
+            key: Vec::new(),
+            value: Vec::new(),
             session: PhantomData,
         }
     }
 
+    /// The format of the cursor's key, e.g. `S` or `SiQ`.
+    #[inline]
+    pub fn key_format(&self) -> &str {
+        self.c_key_format().to_str().unwrap()
+    }
+
+    /// The format of the cursor's value, e.g. `S` or `SiQ`.
+    #[inline]
+    pub fn value_format(&self) -> &str {
+        self.c_value_format().to_str().unwrap()
+    }
+
+    #[inline]
+    fn c_key_format(&self) -> &CStr {
+        let cursor = self.inner.expect("cursor is null");
+        unsafe { CStr::from_ptr((*cursor).key_format) }
+    }
+
+    #[inline]
+    fn c_value_format(&self) -> &CStr {
+        let cursor = self.inner.expect("cursor is null");
+        unsafe { CStr::from_ptr((*cursor).value_format) }
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *mut WT_CURSOR {
+        self.inner.expect("cursor is null")
+    }
+
+    #[inline]
+    fn c_session(&self) -> *mut WT_SESSION {
+        let cursor = self.inner.expect("cursor is null");
+        unsafe { (*cursor).session }
+    }
+
+    #[inline]
+    fn c_uri(&self) -> &CStr {
+        let cursor = self.inner.expect("cursor is null");
+        unsafe { CStr::from_ptr((*cursor).uri) }
+    }
+
+    /// Open another cursor on the same object, in the same session.
+    fn open_sibling(&self) -> Result<Cursor<'a>> {
+        let session = self.c_session();
+        let mut sibling: *mut WT_CURSOR = ptr::null_mut();
+        unsafe {
+            let open_cursor = (*session).open_cursor.expect("null function pointer");
+            wt_try!(open_cursor(
+                session,
+                self.c_uri().as_ptr(),
+                ptr::null_mut(),
+                ptr::null(),
+                &mut sibling as *mut *mut WT_CURSOR
+            ));
+            assert!(!sibling.is_null());
+            Ok(Cursor::new_unchecked(sibling))
+        }
+    }
+
+    /// Compare the keys of the records two cursors are positioned on, in the
+    /// order of their object, which follows its collator.
+    ///
+    /// Both cursors must be on the same object.
+    pub fn compare(&self, other: &Cursor) -> Result<Ordering> {
+        let (cursor, compare) = cursor_api!(self, compare);
+        let mut cmp: c_int = 0;
+        unsafe {
+            wt_try!(compare(cursor, other.as_ptr(), &mut cmp as *mut c_int));
+        }
+        Ok(cmp.cmp(&0))
+    }
+
+    /// Set the key for the next operation, packed with the cursor's key format.
+    pub fn set_key<K: Pack + ?Sized>(&mut self, key: &K) -> Result<()> {
+        let key = unsafe { pack::pack_with(self.c_session(), self.c_key_format(), key)? };
+        self.set_packed_key(key);
+        Ok(())
+    }
+
+    /// Set the value for the next operation, packed with the cursor's value
+    /// format.
+    pub fn set_value<V: Pack + ?Sized>(&mut self, value: &V) -> Result<()> {
+        let value = unsafe { pack::pack_with(self.c_session(), self.c_value_format(), value)? };
+        self.set_packed_value(value);
+        Ok(())
+    }
+
+    /// Get the key of the current record, unpacked with the cursor's key
+    /// format.
+    pub fn get_key<K: Unpack>(&self) -> Result<K> {
+        self.unpack_key(self.get_raw_key()?)
+    }
+
+    /// Get the value of the current record, unpacked with the cursor's value
+    /// format.
+    pub fn get_value<V: Unpack>(&self) -> Result<V> {
+        self.unpack_value(self.get_raw_value()?)
+    }
+
+    pub(crate) fn set_packed_key(&mut self, key: Vec<u8>) {
+        let (cursor, set_key) = cursor_api!(self, set_key);
+        self.key = key;
+        let item = pack::new_item(&self.key);
+        unsafe {
+            with_raw(cursor, || set_key(cursor, &item as *const WT_ITEM));
+        }
+    }
+
+    pub(crate) fn set_packed_value(&mut self, value: Vec<u8>) {
+        let (cursor, set_value) = cursor_api!(self, set_value);
+        self.value = value;
+        let item = pack::new_item(&self.value);
+        unsafe {
+            with_raw(cursor, || set_value(cursor, &item as *const WT_ITEM));
+        }
+    }
+
+    /// Set the key for the next operation from its packed form.
+    pub fn set_raw_key(&mut self, key: &[u8]) {
+        let mut buffer = std::mem::take(&mut self.key);
+        buffer.clear();
+        buffer.extend_from_slice(key);
+        self.set_packed_key(buffer);
+    }
+
+    /// Set the value for the next operation from its packed form.
+    pub fn set_raw_value(&mut self, value: &[u8]) {
+        let mut buffer = std::mem::take(&mut self.value);
+        buffer.clear();
+        buffer.extend_from_slice(value);
+        self.set_packed_value(buffer);
+    }
+
+    /// Get the key of the current record in its packed form, without copying.
+    ///
+    /// The slice is borrowed from WiredTiger's buffer and is only valid until
+    /// the cursor moves or the session resets it, so it borrows the cursor,
+    /// which borrows the session: operations like `next` or `search` cannot
+    /// be called while it is alive, nor can `Session::reset`,
+    /// `commit_transaction` or `rollback_transaction`, which take the session
+    /// mutably. Keys of format `u` are the stored bytes as is.
+    ///
+    /// ```compile_fail
+    /// # use wiredtiger::Connection;
+    /// # let conn = Connection::open("target/wt_doc_raw", "create").unwrap();
+    /// let mut session = conn.open_session("").unwrap();
+    /// session.begin_transaction("").unwrap();
+    /// let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+    /// cursor.set_key("a").unwrap();
+    /// cursor.search().unwrap();
+    /// let key = cursor.get_raw_key().unwrap();
+    /// session.commit_transaction("").unwrap();
+    /// assert_eq!(key, b"a\0");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use wiredtiger::Connection;
+    /// # let conn = Connection::open("target/wt_doc_raw", "create").unwrap();
+    /// let mut session = conn.open_session("").unwrap();
+    /// let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+    /// cursor.set_key("a").unwrap();
+    /// cursor.search().unwrap();
+    /// let key = cursor.get_raw_key().unwrap();
+    /// session.reset().unwrap();
+    /// assert_eq!(key, b"a\0");
+    /// ```
+    pub fn get_raw_key(&self) -> Result<&[u8]> {
+        let (cursor, get_key) = cursor_api!(self, get_key);
+        let mut item = pack::new_item(&[]);
+        unsafe {
+            wt_try!(with_raw(cursor, || get_key(
+                cursor,
+                &mut item as *mut WT_ITEM
+            )));
+            Ok(pack::item_as_slice(&item))
+        }
+    }
+
+    /// Get the value of the current record in its packed form, without
+    /// copying.
+    ///
+    /// Like `get_raw_key`, the slice is only valid until the cursor moves.
+    pub fn get_raw_value(&self) -> Result<&[u8]> {
+        let (cursor, get_value) = cursor_api!(self, get_value);
+        let mut item = pack::new_item(&[]);
+        unsafe {
+            wt_try!(with_raw(cursor, || get_value(
+                cursor,
+                &mut item as *mut WT_ITEM
+            )));
+            Ok(pack::item_as_slice(&item))
+        }
+    }
+
+    /// Get the key and the value of the current record in their packed forms,
+    /// without copying.
+    pub fn get_raw_key_value(&self) -> Result<(&[u8], &[u8])> {
+        Ok((self.get_raw_key()?, self.get_raw_value()?))
+    }
+
+    #[inline]
+    fn unpack_key<K: Unpack>(&self, key: &[u8]) -> Result<K> {
+        unsafe { pack::unpack_with(self.c_session(), self.c_key_format(), key) }
+    }
+
+    #[inline]
+    fn unpack_key_prefix<K: Unpack>(&self, key: &[u8]) -> Result<(K, usize)> {
+        unsafe { pack::unpack_prefix_with(self.c_session(), self.c_key_format(), key) }
+    }
+
+    #[inline]
+    fn unpack_value<V: Unpack>(&self, value: &[u8]) -> Result<V> {
+        unsafe { pack::unpack_with(self.c_session(), self.c_value_format(), value) }
+    }
+
+    /// Move the cursor to the next record.
+    ///
+    /// Returns `false` if there are no more records.
+    #[inline]
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<bool> {
+        let (cursor, next) = cursor_api!(self, next);
+        unsafe { found(next(cursor)) }
+    }
+
+    /// Move the cursor to the previous record.
+    ///
+    /// Returns `false` if there are no more records.
+    #[inline]
+    pub fn prev(&mut self) -> Result<bool> {
+        let (cursor, prev) = cursor_api!(self, prev);
+        unsafe { found(prev(cursor)) }
+    }
+
+    /// Reset the cursor, releasing its position.
+    #[inline]
+    pub fn reset(&mut self) -> Result<()> {
+        let (cursor, reset) = cursor_api!(self, reset);
+        unsafe {
+            wt_try!(reset(cursor));
+        }
+        Ok(())
+    }
+
+    /// Position the cursor on the record matching the key set by `set_key`.
+    ///
+    /// Returns `false` if the key does not exist.
+    #[inline]
+    pub fn search(&mut self) -> Result<bool> {
+        let (cursor, search) = cursor_api!(self, search);
+        unsafe { found(search(cursor)) }
+    }
+
+    /// Position the cursor on the record matching the key set by `set_key`,
+    /// or on an adjacent record if the key does not exist.
+    ///
+    /// Returns `None` if the table is empty.
+    pub fn search_near(&mut self) -> Result<Option<SearchNear>> {
+        let (cursor, search_near) = cursor_api!(self, search_near);
+        let mut exact: c_int = 0;
+        unsafe {
+            if !found(search_near(cursor, &mut exact as *mut c_int))? {
+                return Ok(None);
+            }
+        }
+        Ok(Some(match exact {
+            e if e < 0 => SearchNear::Smaller,
+            0 => SearchNear::Exact,
+            _ => SearchNear::Larger,
+        }))
+    }
+
+    /// Insert the record set by `set_key` and `set_value`.
+    ///
+    /// If the cursor was opened with `overwrite=false`, returns
+    /// `Error::DuplicateKey` when the key already exists.
+    #[inline]
+    pub fn insert(&mut self) -> Result<()> {
+        let (cursor, insert) = cursor_api!(self, insert);
+        unsafe {
+            wt_try!(insert(cursor));
+        }
+        Ok(())
+    }
+
+    /// Update the record set by `set_key` and `set_value`.
+    ///
+    /// If the cursor was opened with `overwrite=false`, returns
+    /// `Error::NotFound` when the key does not exist.
+    #[inline]
+    pub fn update(&mut self) -> Result<()> {
+        let (cursor, update) = cursor_api!(self, update);
+        unsafe {
+            wt_try!(update(cursor));
+        }
+        Ok(())
+    }
+
+    /// Remove the record set by `set_key`.
+    ///
+    /// If the cursor was opened with `overwrite=false`, returns
+    /// `Error::NotFound` when the key does not exist.
+    #[inline]
+    pub fn remove(&mut self) -> Result<()> {
+        let (cursor, remove) = cursor_api!(self, remove);
+        unsafe {
+            wt_try!(remove(cursor));
+        }
+        Ok(())
+    }
+
+    /// Reserve the record set by `set_key` in the running transaction, so
+    /// that concurrent updates to it conflict.
+    ///
+    /// Returns `Error::NotFound` when the key does not exist.
+    #[inline]
+    pub fn reserve(&mut self) -> Result<()> {
+        let (cursor, reserve) = cursor_api!(self, reserve);
+        unsafe {
+            wt_try!(reserve(cursor));
+        }
+        Ok(())
+    }
+
+    /// Set or clear bounds on the cursor, e.g. `action=set,bound=lower`.
+    ///
+    /// The key of the bound is the one set by `set_key`.
+    pub fn bound<C: AsRef<str>>(&mut self, config: C) -> Result<()> {
+        let (cursor, bound) = cursor_api!(self, bound);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        unsafe {
+            wt_try!(bound(cursor, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Iterate over all records in key order.
+    ///
+    /// The cursor is reset before the iteration starts and when the iterator
+    /// is dropped.
+    pub fn iter<K: Unpack, V: Unpack>(&mut self) -> Result<Iter<'_, 'a, K, V>> {
+        Iter::new(self, false, Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Iterate over all records in reverse key order.
+    pub fn iter_rev<K: Unpack, V: Unpack>(&mut self) -> Result<Iter<'_, 'a, K, V>> {
+        Iter::new(self, true, Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Iterate over the records whose keys are within `range`, in key order.
+    ///
+    /// The range limits are pushed into WiredTiger with `bound` when the
+    /// cursor supports it. Otherwise keys are compared in the order of the
+    /// table, which follows its collator, except on index cursors where they
+    /// are ordered by their packed bytes.
+    pub fn range<K, V, Q, R>(&mut self, range: R) -> Result<Iter<'_, 'a, K, V>>
+    where
+        K: Unpack,
+        V: Unpack,
+        Q: Pack + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (lower, upper) = self.pack_range(range)?;
+        Iter::new(self, false, lower, upper)
+    }
+
+    /// Iterate over the records whose keys are within `range`, in reverse key
+    /// order.
+    pub fn range_rev<K, V, Q, R>(&mut self, range: R) -> Result<Iter<'_, 'a, K, V>>
+    where
+        K: Unpack,
+        V: Unpack,
+        Q: Pack + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (lower, upper) = self.pack_range(range)?;
+        Iter::new(self, true, lower, upper)
+    }
+
+    fn pack_range<Q, R>(&self, range: R) -> Result<(PackedBound, PackedBound)>
+    where
+        Q: Pack + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let pack_bound = |bound: Bound<&Q>| -> Result<PackedBound> {
+            let pack_key =
+                |key: &Q| unsafe { pack::pack_with(self.c_session(), self.c_key_format(), key) };
+            Ok(match bound {
+                Bound::Included(key) => Bound::Included(pack_key(key)?),
+                Bound::Excluded(key) => Bound::Excluded(pack_key(key)?),
+                Bound::Unbounded => Bound::Unbounded,
+            })
+        };
+        Ok((
+            pack_bound(range.start_bound())?,
+            pack_bound(range.end_bound())?,
+        ))
+    }
+
     pub fn close(&mut self) -> Result<()> {
         if let Some(cursor) = self.inner {
             debug_assert!(!cursor.is_null());
@@ -57,9 +512,255 @@ impl<'a> Drop for Cursor<'a> {
     }
 }
 
+/// The end of a range that WiredTiger does not enforce, held by a cursor
+/// positioned on the record nearest to it, so records are compared in the
+/// table's order, which follows its collator. The start of the range is
+/// enforced by `Iter::seek`.
+struct Limit<'a> {
+    cursor: Cursor<'a>,
+    /// The order of the records within the range relative to the record of
+    /// `cursor`.
+    side: Ordering,
+    /// Whether the record of `cursor` is itself within the range.
+    inclusive: bool,
+}
+
+impl<'a> Limit<'a> {
+    /// Position a sibling of `cursor` near `bound`, or return `None` if the
+    /// table is empty.
+    fn new(cursor: &Cursor<'a>, bound: &PackedBound, side: Ordering) -> Result<Option<Self>> {
+        let (key, inclusive) = match bound {
+            Bound::Included(key) => (key.clone(), true),
+            Bound::Excluded(key) => (key.clone(), false),
+            Bound::Unbounded => return Ok(None),
+        };
+        let mut cursor = cursor.open_sibling()?;
+        cursor.set_packed_key(key);
+        let inclusive = match cursor.search_near()? {
+            Some(SearchNear::Exact) => inclusive,
+            Some(SearchNear::Smaller) => side == Ordering::Less,
+            Some(SearchNear::Larger) => side == Ordering::Greater,
+            None => return Ok(None),
+        };
+        Ok(Some(Self {
+            cursor,
+            side,
+            inclusive,
+        }))
+    }
+
+    fn admits(&self, cursor: &Cursor) -> Result<bool> {
+        let order = cursor.compare(&self.cursor)?;
+        Ok(order == self.side || (order == Ordering::Equal && self.inclusive))
+    }
+}
+
+/// An iterator over the records of a cursor, yielding decoded keys and
+/// values.
+pub struct Iter<'c, 'a, K, V> {
+    cursor: &'c mut Cursor<'a>,
+    reverse: bool,
+    lower: PackedBound,
+    upper: PackedBound,
+    // Whether WiredTiger enforces the bounds itself.
+    bounded: bool,
+    // The end checked otherwise, unless keys are compared by their bytes.
+    end: Option<Limit<'a>>,
+    started: bool,
+    done: bool,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'c, 'a, K: Unpack, V: Unpack> Iter<'c, 'a, K, V> {
+    fn new(
+        cursor: &'c mut Cursor<'a>,
+        reverse: bool,
+        lower: PackedBound,
+        upper: PackedBound,
+    ) -> Result<Self> {
+        cursor.reset()?;
+        let mut iter = Self {
+            cursor,
+            reverse,
+            lower,
+            upper,
+            bounded: false,
+            end: None,
+            started: false,
+            done: false,
+            marker: PhantomData,
+        };
+        iter.bounded = iter.push_bounds();
+        if !iter.bounded {
+            // Clear any bound that was set before the failure.
+            iter.cursor.reset()?;
+            if !iter.by_bytes() {
+                iter.end = if reverse {
+                    Limit::new(iter.cursor, &iter.lower, Ordering::Greater)?
+                } else {
+                    Limit::new(iter.cursor, &iter.upper, Ordering::Less)?
+                };
+            }
+        }
+        Ok(iter)
+    }
+
+    /// Try to push the range limits into WiredTiger.
+    fn push_bounds(&mut self) -> bool {
+        let cursor = self.cursor.inner.expect("cursor is null");
+        if unsafe { (*cursor).bound.is_none() } {
+            return false;
+        }
+
+        let limits = [("lower", self.lower.clone()), ("upper", self.upper.clone())];
+        for (bound, limit) in limits.iter().cloned() {
+            let (key, inclusive) = match limit {
+                Bound::Included(key) => (key, true),
+                Bound::Excluded(key) => (key, false),
+                Bound::Unbounded => continue,
+            };
+            self.cursor.set_packed_key(key);
+            let config = format!("action=set,bound={},inclusive={}", bound, inclusive);
+            if self.cursor.bound(config).is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    #[inline]
+    fn step(&mut self) -> Result<bool> {
+        if self.reverse {
+            self.cursor.prev()
+        } else {
+            self.cursor.next()
+        }
+    }
+
+    /// Position the cursor on the first record of the iteration.
+    fn seek(&mut self) -> Result<bool> {
+        let start = if self.reverse {
+            &self.upper
+        } else {
+            &self.lower
+        };
+        let (key, inclusive) = match start {
+            Bound::Included(key) => (key.clone(), true),
+            Bound::Excluded(key) => (key.clone(), false),
+            Bound::Unbounded => return self.step(),
+        };
+        if self.bounded {
+            return self.step();
+        }
+
+        self.cursor.set_packed_key(key);
+        let near = match self.cursor.search_near()? {
+            Some(near) => near,
+            None => return Ok(false),
+        };
+        let behind = if self.reverse {
+            SearchNear::Larger
+        } else {
+            SearchNear::Smaller
+        };
+        if near == behind || (near == SearchNear::Exact && !inclusive) {
+            self.step()
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Whether the keys are compared by their packed bytes, on index cursors
+    /// whose keys end with the primary key columns the bounds do not cover.
+    fn by_bytes(&self) -> bool {
+        self.cursor.c_uri().to_bytes().starts_with(b"index:")
+    }
+
+    /// Whether the record the cursor is on is within the range, if WiredTiger
+    /// does not enforce it. `key` is the packed key without any primary key
+    /// columns of an index.
+    fn contains(&self, key: &[u8]) -> Result<bool> {
+        if self.bounded {
+            return Ok(true);
+        }
+        if !self.by_bytes() {
+            return match &self.end {
+                Some(end) => end.admits(self.cursor),
+                None => Ok(true),
+            };
+        }
+
+        let above_lower = match &self.lower {
+            Bound::Included(lower) => key >= lower.as_slice(),
+            Bound::Excluded(lower) => key > lower.as_slice(),
+            Bound::Unbounded => true,
+        };
+        let below_upper = match &self.upper {
+            Bound::Included(upper) => key <= upper.as_slice(),
+            Bound::Excluded(upper) => key < upper.as_slice(),
+            Bound::Unbounded => true,
+        };
+        Ok(above_lower && below_upper)
+    }
+
+    fn advance(&mut self) -> Result<Option<(K, V)>> {
+        let positioned = if self.started {
+            self.step()?
+        } else {
+            self.started = true;
+            self.seek()?
+        };
+        if !positioned {
+            return Ok(None);
+        }
+
+        // The raw keys of index cursors end with the primary key columns, so
+        // only the part covered by the key format is compared to the bounds.
+        let raw_key = self.cursor.get_raw_key()?;
+        let (key, used) = self.cursor.unpack_key_prefix(raw_key)?;
+        if !self.contains(&raw_key[..used])? {
+            return Ok(None);
+        }
+        let value = self.cursor.get_value()?;
+        Ok(Some((key, value)))
+    }
+}
+
+impl<'c, 'a, K: Unpack, V: Unpack> Iterator for Iter<'c, 'a, K, V> {
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.advance() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl<'c, 'a, K, V> Drop for Iter<'c, 'a, K, V> {
+    fn drop(&mut self) {
+        // Resetting the cursor also clears its bounds.
+        if let Err(error) = self.cursor.reset() {
+            eprintln!("error happened when auto reset cursor: {}", error);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Connection;
+    use crate::error::Error;
+    use crate::{Connection, SearchNear};
+    use std::ops::Bound;
     use test_utils;
 
     #[test]
@@ -75,4 +776,179 @@ mod tests {
         cursor.close().unwrap();
         session.drop("table:test_table", "").unwrap();
     }
+
+    #[test]
+    fn test_set_get_key_value() {
+        let home = "target/wt_set_get_key_value";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=Sq,value_format=uS")
+            .unwrap();
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        assert_eq!(cursor.key_format(), "Sq");
+        assert_eq!(cursor.value_format(), "uS");
+
+        cursor.set_key(&("key", -1i64)).unwrap();
+        cursor.set_value(&(&b"\x00\x01"[..], "value")).unwrap();
+        let key: (String, i64) = cursor.get_key().unwrap();
+        assert_eq!(key, ("key".to_string(), -1));
+        let value: (Vec<u8>, String) = cursor.get_value().unwrap();
+        assert_eq!(value, (vec![0, 1], "value".to_string()));
+
+        assert!(matches!(cursor.set_key("a\0b"), Err(Error::Codec(_))));
+    }
+
+    #[test]
+    fn test_empty_table_position() {
+        let home = "target/wt_empty_table_position";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=S")
+            .unwrap();
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        assert!(!cursor.next().unwrap());
+        assert!(!cursor.prev().unwrap());
+        cursor.reset().unwrap();
+        cursor.set_key("key").unwrap();
+        assert!(!cursor.search().unwrap());
+        cursor.set_key("key").unwrap();
+        assert_eq!(cursor.search_near().unwrap(), None);
+    }
+
+    #[test]
+    fn test_insert_update_remove() {
+        let home = "target/wt_insert_update_remove";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=q")
+            .unwrap();
+        let mut cursor = session
+            .open_cursor("table:test_table", "overwrite=false")
+            .unwrap();
+
+        for (key, value) in &[("a", 1i64), ("c", 3), ("e", 5)] {
+            cursor.set_key(*key).unwrap();
+            cursor.set_value(value).unwrap();
+            cursor.insert().unwrap();
+        }
+
+        cursor.set_key("a").unwrap();
+        cursor.set_value(&0i64).unwrap();
+        assert!(matches!(cursor.insert(), Err(Error::DuplicateKey)));
+
+        cursor.set_key("c").unwrap();
+        cursor.set_value(&30i64).unwrap();
+        cursor.update().unwrap();
+
+        cursor.set_key("b").unwrap();
+        cursor.set_value(&2i64).unwrap();
+        assert!(matches!(cursor.update(), Err(Error::NotFound)));
+
+        cursor.set_key("e").unwrap();
+        cursor.remove().unwrap();
+        cursor.set_key("e").unwrap();
+        assert!(matches!(cursor.remove(), Err(Error::NotFound)));
+
+        cursor.set_key("c").unwrap();
+        assert!(cursor.search().unwrap());
+        assert_eq!(cursor.get_value::<i64>().unwrap(), 30);
+
+        cursor.set_key("b").unwrap();
+        let expected = match cursor.search_near().unwrap() {
+            Some(SearchNear::Smaller) => "a",
+            Some(SearchNear::Larger) => "c",
+            other => panic!("unexpected search_near result: {:?}", other),
+        };
+        assert_eq!(cursor.get_key::<String>().unwrap(), expected);
+
+        cursor.reset().unwrap();
+        let mut keys = Vec::new();
+        while cursor.next().unwrap() {
+            keys.push(cursor.get_key::<String>().unwrap());
+        }
+        assert_eq!(keys, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let home = "target/wt_iter_range";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=q,value_format=S")
+            .unwrap();
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        for i in 0..10i64 {
+            cursor.set_key(&i).unwrap();
+            cursor.set_value(&i.to_string()).unwrap();
+            cursor.insert().unwrap();
+        }
+
+        let all: Vec<(i64, String)> = cursor.iter().unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[3], (3, "3".to_string()));
+
+        let keys: Vec<i64> = cursor
+            .iter_rev::<i64, String>()
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, (0..10).rev().collect::<Vec<_>>());
+
+        let keys: Vec<i64> = cursor
+            .range::<i64, String, _, _>(3i64..6)
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![3, 4, 5]);
+
+        let keys: Vec<i64> = cursor
+            .range_rev::<i64, String, _, _>(7i64..)
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![9, 8, 7]);
+
+        let keys: Vec<i64> = cursor
+            .range::<i64, String, _, _>((Bound::Excluded(2i64), Bound::Included(4i64)))
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_raw_key_value() {
+        use crate::config::CursorConfig;
+        use crate::pack;
+
+        let home = "target/wt_raw_key_value";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=Si,value_format=u")
+            .unwrap();
+        let config = CursorConfig::new().raw(true).to_string();
+        let mut cursor = session.open_cursor("table:test_table", config).unwrap();
+
+        let key = pack::pack("Si", &("a", 1i32)).unwrap();
+        cursor.set_raw_key(&key);
+        cursor.set_raw_value(b"hello");
+        cursor.insert().unwrap();
+
+        cursor.set_key(&("a", 1i32)).unwrap();
+        assert!(cursor.search().unwrap());
+        let (raw_key, raw_value) = cursor.get_raw_key_value().unwrap();
+        assert_eq!(raw_key, key.as_slice());
+        assert_eq!(raw_value, b"hello");
+        assert_eq!(cursor.get_value::<Vec<u8>>().unwrap(), b"hello".to_vec());
+    }
 }
@@ -17,6 +17,8 @@
 use std::borrow::Cow;
 use std::ffi::CStr;
 use std::fmt::Formatter;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
 use wiredtiger_sys;
 
 /// WiredTiger's Error.
@@ -33,6 +35,35 @@ pub enum Error {
     CacheFull,
     PrepareConflict,
     TrySalvage,
+    /// Failed to encode or decode a value.
+    Codec(String),
+}
+
+impl Error {
+    /// Whether the operation failed because of a conflict with another
+    /// transaction, so running it again in a new transaction may succeed.
+    #[inline]
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Error::Rollback | Error::PrepareConflict)
+    }
+
+    /// The error code WiredTiger uses for this error.
+    pub(crate) fn code(&self) -> i32 {
+        match self {
+            Error::System(i) => *i,
+            Error::Rollback => wiredtiger_sys::WT_ROLLBACK,
+            Error::DuplicateKey => wiredtiger_sys::WT_DUPLICATE_KEY,
+            Error::GeneralError => wiredtiger_sys::WT_ERROR,
+            Error::NotFound => wiredtiger_sys::WT_NOTFOUND,
+            Error::Panic => wiredtiger_sys::WT_PANIC,
+            Error::Restart => wiredtiger_sys::WT_RESTART,
+            Error::RunRecovery => wiredtiger_sys::WT_RUN_RECOVERY,
+            Error::CacheFull => wiredtiger_sys::WT_CACHE_FULL,
+            Error::PrepareConflict => wiredtiger_sys::WT_PREPARE_CONFLICT,
+            Error::TrySalvage => wiredtiger_sys::WT_TRY_SALVAGE,
+            Error::Codec(_) => EINVAL,
+        }
+    }
 }
 
 impl std::error::Error for Error {}
@@ -55,6 +86,7 @@ impl std::fmt::Display for Error {
                 write!(f, "{}", strerror(wiredtiger_sys::WT_PREPARE_CONFLICT))
             }
             Error::TrySalvage => write!(f, "{}", strerror(wiredtiger_sys::WT_TRY_SALVAGE)),
+            Error::Codec(msg) => write!(f, "codec error: {}", msg),
         }
     }
 }
@@ -78,6 +110,12 @@ impl From<i32> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::System(error.raw_os_error().unwrap_or(EIO))
+    }
+}
+
 unsafe fn wt_strerror<'a>(error: i32) -> Cow<'a, str> {
     let cstr_error = wiredtiger_sys::wiredtiger_strerror(error);
     CStr::from_ptr(cstr_error).to_string_lossy()
@@ -85,6 +123,71 @@ unsafe fn wt_strerror<'a>(error: i32) -> Cow<'a, str> {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Run `f`, a Rust implementation of a WiredTiger callback, and return its
+/// error code. A panic becomes `WT_ERROR`, since unwinding into WiredTiger is
+/// undefined behavior.
+pub(crate) fn catch_callback<F: FnOnce() -> Result<()>>(operation: &str, f: F) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(error)) => error.code(),
+        Err(_) => {
+            eprintln!("error happened when {}: callback panicked", operation);
+            wiredtiger_sys::WT_ERROR
+        }
+    }
+}
+
+/// How transactions failing with a retryable error are retried.
+///
+/// The delay before a retry starts at `initial_backoff` and doubles after
+/// every attempt, up to `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn never() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// The delay before the `attempt`th retry, starting from 1.
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let shift = attempt.saturating_sub(1).min(31) as u32;
+        self.initial_backoff
+            .checked_mul(1 << shift)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// System error code of an I/O error.
+pub(crate) const EIO: i32 = 5;
+
+/// System error code of an invalid argument.
+pub(crate) const EINVAL: i32 = 22;
+
+/// System error code of a busy resource.
+pub(crate) const EBUSY: i32 = 16;
+
+/// System error code of running out of memory.
+pub(crate) const ENOMEM: i32 = 12;
+
 macro_rules! wt_try {
     ($expr:expr) => {
         let errcode: i32 = $expr;
@@ -96,3 +199,37 @@ macro_rules! wt_try {
         $crate::wt_try!($expr)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(1));
+        assert_eq!(policy.backoff(2), Duration::from_millis(2));
+        assert_eq!(policy.backoff(5), Duration::from_millis(16));
+        assert_eq!(policy.backoff(8), Duration::from_millis(100));
+        assert_eq!(policy.backoff(usize::MAX), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(Error::Rollback.is_retryable());
+        assert!(Error::PrepareConflict.is_retryable());
+        assert!(!Error::NotFound.is_retryable());
+    }
+
+    #[test]
+    fn test_code() {
+        assert!(matches!(
+            Error::from(Error::Rollback.code()),
+            Error::Rollback
+        ));
+        assert!(matches!(
+            Error::from(Error::System(5).code()),
+            Error::System(5)
+        ));
+    }
+}
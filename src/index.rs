@@ -0,0 +1,201 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Secondary indices of record tables.
+//!
+//! An index cursor is keyed on the index columns and projects every column of
+//! the table, so it returns whole records.
+
+use crate::error::Result;
+use crate::pack::{Pack, Unpack};
+use crate::record::Record;
+use crate::{uri, Cursor, Iter, Session};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// The index `name` on the table of the `R` records.
+pub struct Index<'s, R> {
+    session: &'s Session<'s>,
+    name: String,
+    record: PhantomData<fn() -> R>,
+}
+
+impl<'s, R: Record> Index<'s, R> {
+    pub(crate) fn new(session: &'s Session<'s>, name: &str) -> Self {
+        Self {
+            session,
+            name: name.to_string(),
+            record: PhantomData,
+        }
+    }
+
+    /// The name of the index.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The URI of the index.
+    pub fn uri(&self) -> String {
+        uri::index(R::TABLE, &self.name)
+    }
+
+    /// Open a cursor on the index, whose index keys are `K`.
+    pub fn cursor<K: Pack + Unpack>(&self) -> Result<IndexCursor<'s, R, K>> {
+        let columns: Vec<&str> = R::KEY_COLUMNS
+            .iter()
+            .chain(R::VALUE_COLUMNS)
+            .copied()
+            .collect();
+        let cursor = self
+            .session
+            .open_index_cursor(R::TABLE, &self.name, &columns, "")?;
+        Ok(IndexCursor {
+            cursor,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// A cursor on an index, looking up `R` records by `K` index keys.
+pub struct IndexCursor<'s, R, K> {
+    cursor: Cursor<'s>,
+    marker: PhantomData<fn() -> (R, K)>,
+}
+
+impl<'s, R: Record, K: Pack + Unpack> IndexCursor<'s, R, K> {
+    /// The underlying cursor.
+    #[inline]
+    pub fn cursor(&mut self) -> &mut Cursor<'s> {
+        &mut self.cursor
+    }
+
+    /// Get the first record whose index key is `key`.
+    pub fn get(&mut self, key: &K) -> Result<Option<R>> {
+        self.cursor.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(None);
+        }
+        let (key, value) = self.cursor.get_value()?;
+        self.cursor.reset()?;
+        Ok(Some(R::from_parts(key, value)))
+    }
+
+    /// Iterate over the records whose index key is `key`.
+    pub fn find(&mut self, key: &K) -> Result<IndexIter<'_, 's, R, K>> {
+        self.range((Bound::Included(key), Bound::Included(key)))
+    }
+
+    /// Iterate over all records in index key order.
+    pub fn iter(&mut self) -> Result<IndexIter<'_, 's, R, K>> {
+        Ok(IndexIter {
+            inner: self.cursor.iter()?,
+        })
+    }
+
+    /// Iterate over the records whose index keys are within `range`, in index
+    /// key order.
+    pub fn range<B: RangeBounds<K>>(&mut self, range: B) -> Result<IndexIter<'_, 's, R, K>> {
+        Ok(IndexIter {
+            inner: self.cursor.range(range)?,
+        })
+    }
+}
+
+/// An iterator over an `IndexCursor`, yielding index keys and records.
+pub struct IndexIter<'c, 's, R: Record, K> {
+    inner: Iter<'c, 's, K, (R::Key, R::Value)>,
+}
+
+impl<'c, 's, R: Record, K: Unpack> Iterator for IndexIter<'c, 's, R, K> {
+    type Item = Result<(K, R)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|record| {
+            record.map(|(index_key, (key, value))| (index_key, R::from_parts(key, value)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::Table;
+    use crate::Connection;
+    use test_utils;
+    use wiredtiger_derive::WtRecord;
+
+    #[derive(Debug, PartialEq, WtRecord)]
+    #[wt(table = "people")]
+    struct Person {
+        #[wt(key)]
+        id: u64,
+        name: String,
+        city: String,
+    }
+
+    #[test]
+    fn test_index() {
+        let home = "target/wt_index";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        let people = Table::<Person>::create(&session).unwrap();
+        let by_name = people.create_index("by_name", &["name"]).unwrap();
+        let by_city = people.create_index("by_city", &["city", "name"]).unwrap();
+        assert_eq!(by_name.uri(), "index:people:by_name");
+
+        let rows = [
+            (1, "carol", "paris"),
+            (2, "alice", "berlin"),
+            (3, "bob", "paris"),
+            (4, "dave", "rome"),
+        ];
+        for (id, name, city) in rows.iter() {
+            let person = Person {
+                id: *id,
+                name: name.to_string(),
+                city: city.to_string(),
+            };
+            people.insert(&person).unwrap();
+        }
+
+        let mut cursor = by_name.cursor::<(String,)>().unwrap();
+        let bob = cursor.get(&("bob".to_string(),)).unwrap().unwrap();
+        assert_eq!(bob.id, 3);
+        assert_eq!(bob.city, "paris");
+        assert_eq!(cursor.get(&("eve".to_string(),)).unwrap(), None);
+        let ids: Vec<u64> = cursor.iter().unwrap().map(|r| r.unwrap().1.id).collect();
+        assert_eq!(ids, vec![2, 3, 1, 4]);
+
+        let mut cursor = by_city.cursor::<(String, String)>().unwrap();
+        let start = ("paris".to_string(), String::new());
+        let names: Vec<String> = cursor
+            .range(start..)
+            .unwrap()
+            .map(|r| r.unwrap().1.name)
+            .collect();
+        assert_eq!(names, vec!["bob", "carol", "dave"]);
+
+        let key = ("paris".to_string(), "carol".to_string());
+        let found: Vec<(String, String)> =
+            cursor.find(&key).unwrap().map(|r| r.unwrap().0).collect();
+        assert_eq!(found, vec![key]);
+
+        let session_cursor = session
+            .open_index_cursor("people", "by_name", &["id"], "")
+            .unwrap();
+        assert_eq!(session_cursor.key_format(), "S");
+        assert_eq!(session_cursor.value_format(), "Q");
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Join cursors, which intersect conditions on the indices of a table.
+//!
+//! ```ignore
+//! let mut join = session.open_join_cursor("people", &["name"])?;
+//!
+//! let mut by_age = session.open_index_cursor("people", "by_age", &[] as &[&str], "")?;
+//! by_age.set_key(&(30u32,))?;
+//! by_age.search()?;
+//! session.join(&mut join, by_age, &JoinConfig::new().compare(JoinCompare::Ge))?;
+//!
+//! for record in join.iter::<(u64,), (String,)>()? {
+//!     let ((id,), (name,)) = record?;
+//! }
+//! ```
+
+use crate::error::Result;
+use crate::pack::Unpack;
+use crate::{Cursor, Iter};
+use wiredtiger_sys::WT_CURSOR;
+
+/// A cursor over the rows of a table matching the joined conditions.
+pub struct JoinCursor<'a> {
+    cursor: Cursor<'a>,
+    // The reference cursors must stay open until the join cursor is closed,
+    // so they are declared, and dropped, after it.
+    refs: Vec<Cursor<'a>>,
+}
+
+impl<'a> JoinCursor<'a> {
+    pub(crate) fn new(cursor: Cursor<'a>) -> Self {
+        Self {
+            cursor,
+            refs: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *mut WT_CURSOR {
+        self.cursor.as_ptr()
+    }
+
+    pub(crate) fn keep(&mut self, ref_cursor: Cursor<'a>) {
+        self.refs.push(ref_cursor);
+    }
+
+    /// The underlying cursor, whose keys are the primary keys of the rows.
+    #[inline]
+    pub fn cursor(&mut self) -> &mut Cursor<'a> {
+        &mut self.cursor
+    }
+
+    /// Iterate over the keys and values of the matching rows.
+    pub fn iter<K: Unpack, V: Unpack>(&mut self) -> Result<Iter<'_, 'a, K, V>> {
+        self.cursor.iter()
+    }
+
+    /// Close the join cursor and then its reference cursors.
+    pub fn close(&mut self) -> Result<()> {
+        self.cursor.close()?;
+        for mut cursor in self.refs.drain(..) {
+            cursor.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{IndexConfig, JoinCompare, JoinConfig};
+    use crate::Connection;
+    use test_utils;
+
+    #[test]
+    fn test_join() {
+        let home = "target/wt_join";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create(
+                "table:people",
+                "key_format=Q,value_format=SIS,columns=(id,name,age,city)",
+            )
+            .unwrap();
+        let by_age = IndexConfig::new().columns(&["age"]);
+        session.create_index("people", "by_age", &by_age).unwrap();
+        let by_city = IndexConfig::new().columns(&["city"]);
+        session.create_index("people", "by_city", &by_city).unwrap();
+
+        let mut cursor = session.open_cursor("table:people", "").unwrap();
+        let rows = [
+            (1u64, "alice", 25u32, "paris"),
+            (2, "bob", 35, "paris"),
+            (3, "carol", 40, "rome"),
+            (4, "dave", 45, "paris"),
+        ];
+        for (id, name, age, city) in rows.iter() {
+            cursor.set_key(&(*id,)).unwrap();
+            cursor.set_value(&(*name, *age, *city)).unwrap();
+            cursor.insert().unwrap();
+        }
+
+        let no_projection: &[&str] = &[];
+        let mut join = session.open_join_cursor("people", &["name"]).unwrap();
+
+        let mut age = session
+            .open_index_cursor("people", "by_age", no_projection, "")
+            .unwrap();
+        age.set_key(&(35u32,)).unwrap();
+        assert!(age.search().unwrap());
+        let config = JoinConfig::new().compare(JoinCompare::Ge);
+        session.join(&mut join, age, &config).unwrap();
+
+        let mut city = session
+            .open_index_cursor("people", "by_city", no_projection, "")
+            .unwrap();
+        city.set_key(&("paris",)).unwrap();
+        assert!(city.search().unwrap());
+        let config = JoinConfig::new().compare(JoinCompare::Eq).bloom(100);
+        session.join(&mut join, city, &config).unwrap();
+
+        let mut names: Vec<String> = join
+            .iter::<(u64,), (String,)>()
+            .unwrap()
+            .map(|r| (r.unwrap().1).0)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["bob", "dave"]);
+        join.close().unwrap();
+    }
+}
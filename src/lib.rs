@@ -14,12 +14,53 @@
 
 //! An idiomatic Rust wrapper for WiredTiger API.
 
+// Lets the code generated by `wiredtiger-derive` refer to `::wiredtiger`
+// inside this crate too.
+extern crate self as wiredtiger;
+
 #[macro_use]
 pub mod error;
+pub mod backup;
+pub mod change;
+pub mod checkpoint;
+pub mod collator;
+pub mod compressor;
+pub mod config;
 pub mod connection;
 pub mod cursor;
+pub mod index;
+pub mod join;
+pub mod log;
+pub mod metadata;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "serde")]
+pub mod ordered;
+pub mod pack;
+pub mod record;
 pub mod session;
+pub mod statistics;
+pub mod timestamp;
+pub mod transaction;
+#[cfg(feature = "serde")]
+pub mod typed;
+pub mod uri;
 
-pub use connection::Connection;
-pub use cursor::Cursor;
+pub use backup::Backup;
+pub use change::ChangeStream;
+pub use checkpoint::CheckpointConfig;
+pub use collator::Collator;
+pub use compressor::Compressor;
+pub use connection::{Connection, RollbackToStable};
+pub use cursor::{Cursor, Iter, SearchNear};
+pub use index::{Index, IndexCursor};
+pub use join::JoinCursor;
+pub use log::LogReader;
+pub use record::{Record, Table};
 pub use session::Session;
+pub use timestamp::Timestamp;
+pub use transaction::Transaction;
+#[cfg(feature = "serde")]
+pub use typed::TypedTable;
+#[cfg(feature = "derive")]
+pub use wiredtiger_derive::WtRecord;
@@ -0,0 +1,282 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading the write-ahead log through `log:` cursors.
+//!
+//! The log must be enabled with the `log` configuration of the connection.
+//! A commit record is returned as one entry per operation of the transaction,
+//! all at the LSN of the record. The keys and values of operations are packed
+//! with the formats of the file they belong to, see `pack::unpack`.
+
+use crate::error::Result;
+use crate::{pack, Cursor};
+use std::fmt::{Display, Formatter};
+use wiredtiger_sys::{
+    WT_LOGOP_CHECKPOINT_START, WT_LOGOP_COL_MODIFY, WT_LOGOP_COL_PUT, WT_LOGOP_COL_REMOVE,
+    WT_LOGOP_COL_TRUNCATE, WT_LOGOP_PREV_LSN, WT_LOGOP_ROW_MODIFY, WT_LOGOP_ROW_PUT,
+    WT_LOGOP_ROW_REMOVE, WT_LOGOP_ROW_TRUNCATE, WT_LOGOP_TXN_TIMESTAMP, WT_LOGREC_CHECKPOINT,
+    WT_LOGREC_COMMIT, WT_LOGREC_FILE_SYNC, WT_LOGREC_MESSAGE, WT_LOGREC_SYSTEM,
+};
+
+/// A log sequence number, i.e. the position of a record in the log.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lsn {
+    pub file: u32,
+    pub offset: u32,
+}
+
+impl Display for Lsn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}][{}]", self.file, self.offset)
+    }
+}
+
+/// The type of an operation of a commit or system record.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogOp {
+    ColModify,
+    ColPut,
+    ColRemove,
+    ColTruncate,
+    RowModify,
+    RowPut,
+    RowRemove,
+    RowTruncate,
+    CheckpointStart,
+    PrevLsn,
+    TxnTimestamp,
+    Other(u32),
+}
+
+impl From<u32> for LogOp {
+    fn from(optype: u32) -> Self {
+        match optype {
+            WT_LOGOP_COL_MODIFY => LogOp::ColModify,
+            WT_LOGOP_COL_PUT => LogOp::ColPut,
+            WT_LOGOP_COL_REMOVE => LogOp::ColRemove,
+            WT_LOGOP_COL_TRUNCATE => LogOp::ColTruncate,
+            WT_LOGOP_ROW_MODIFY => LogOp::RowModify,
+            WT_LOGOP_ROW_PUT => LogOp::RowPut,
+            WT_LOGOP_ROW_REMOVE => LogOp::RowRemove,
+            WT_LOGOP_ROW_TRUNCATE => LogOp::RowTruncate,
+            WT_LOGOP_CHECKPOINT_START => LogOp::CheckpointStart,
+            WT_LOGOP_PREV_LSN => LogOp::PrevLsn,
+            WT_LOGOP_TXN_TIMESTAMP => LogOp::TxnTimestamp,
+            optype => LogOp::Other(optype),
+        }
+    }
+}
+
+/// A record of the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogRecord {
+    /// An operation of a committed transaction.
+    Commit {
+        txn_id: u64,
+        op: LogOp,
+        /// The ID of the file, as in the `id` of its metadata.
+        file_id: u32,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    /// The end of a checkpoint starting at `lsn`.
+    Checkpoint { lsn: Lsn },
+    /// The start or the end of a sync of the file `file_id`.
+    FileSync { file_id: u32, start: bool },
+    /// A message written with `WT_SESSION::log_printf`.
+    Message(String),
+    /// An operation of a record written by WiredTiger itself.
+    System { op: LogOp },
+    /// A record of another type, with its packed contents.
+    Other { rectype: u32, value: Vec<u8> },
+}
+
+/// A record with its position in the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub lsn: Lsn,
+    pub record: LogRecord,
+}
+
+type LogKey = (u32, u32, u32);
+type LogValue = (i64, u32, u32, u32, Vec<u8>, Vec<u8>);
+
+/// Decode the record at the current position of a `log:` cursor.
+fn decode((file, offset, _): LogKey, value: LogValue) -> Result<LogEntry> {
+    let (txn_id, rectype, optype, file_id, key, value) = value;
+    // Records other than commit and system records are returned whole,
+    // without their type.
+    let record = match rectype {
+        WT_LOGREC_COMMIT => LogRecord::Commit {
+            txn_id: txn_id as u64,
+            op: LogOp::from(optype),
+            file_id,
+            key,
+            value,
+        },
+        WT_LOGREC_SYSTEM => LogRecord::System {
+            op: LogOp::from(optype),
+        },
+        WT_LOGREC_CHECKPOINT => {
+            let (file, offset, _, _) = pack::unpack::<(u32, u32, u32, Vec<u8>)>("IIIu", &value)?;
+            LogRecord::Checkpoint {
+                lsn: Lsn { file, offset },
+            }
+        }
+        WT_LOGREC_FILE_SYNC => {
+            let (file_id, start) = pack::unpack::<(u32, i32)>("Ii", &value)?;
+            LogRecord::FileSync {
+                file_id,
+                start: start != 0,
+            }
+        }
+        WT_LOGREC_MESSAGE => LogRecord::Message(pack::unpack::<String>("S", &value)?),
+        rectype => LogRecord::Other { rectype, value },
+    };
+    Ok(LogEntry {
+        lsn: Lsn { file, offset },
+        record,
+    })
+}
+
+/// Iterates over the records of the log, oldest first.
+pub struct LogReader<'s> {
+    cursor: Cursor<'s>,
+    // Whether the cursor is on a record not returned yet, after `seek`.
+    positioned: bool,
+}
+
+impl<'s> LogReader<'s> {
+    pub(crate) fn new(cursor: Cursor<'s>) -> Self {
+        Self {
+            cursor,
+            positioned: false,
+        }
+    }
+
+    #[inline]
+    pub fn cursor(&mut self) -> &mut Cursor<'s> {
+        &mut self.cursor
+    }
+
+    /// Continue reading at the record `lsn`.
+    ///
+    /// Returns `false` if there is no record at `lsn`, in which case reading
+    /// starts over from the oldest record.
+    pub fn seek(&mut self, lsn: Lsn) -> Result<bool> {
+        self.cursor.set_key(&(lsn.file, lsn.offset, 0u32))?;
+        self.positioned = self.cursor.search()?;
+        if !self.positioned {
+            self.cursor.reset()?;
+        }
+        Ok(self.positioned)
+    }
+
+    /// Read the next record, or `None` at the end of the log.
+    pub fn next_entry(&mut self) -> Result<Option<LogEntry>> {
+        if self.positioned {
+            self.positioned = false;
+        } else if !self.cursor.next()? {
+            return Ok(None);
+        }
+        let key = self.cursor.get_key::<LogKey>()?;
+        let value = self.cursor.get_value::<LogValue>()?;
+        decode(key, value).map(Some)
+    }
+
+    #[inline]
+    pub fn close(mut self) -> Result<()> {
+        self.cursor.close()
+    }
+}
+
+impl<'s> Iterator for LogReader<'s> {
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckpointConfig, Connection};
+    use test_utils;
+
+    #[test]
+    fn test_log_op() {
+        assert_eq!(LogOp::from(WT_LOGOP_ROW_PUT), LogOp::RowPut);
+        assert_eq!(LogOp::from(WT_LOGOP_ROW_REMOVE), LogOp::RowRemove);
+        assert_eq!(LogOp::from(1000), LogOp::Other(1000));
+    }
+
+    #[test]
+    fn test_log_reader() {
+        let home = "target/wt_log_reader";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create,log=(enabled=true)").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:log", "key_format=S,value_format=S")
+            .unwrap();
+        let mut cursor = session.open_cursor("table:log", "").unwrap();
+        cursor.set_key("a").unwrap();
+        cursor.set_value("1").unwrap();
+        cursor.insert().unwrap();
+        cursor.close().unwrap();
+
+        let mut reader = session.open_log_reader().unwrap();
+        let entries = reader.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        let (lsn, key, value) = entries
+            .iter()
+            .find_map(|entry| match &entry.record {
+                LogRecord::Commit {
+                    op: LogOp::RowPut,
+                    key,
+                    value,
+                    ..
+                } => Some((entry.lsn, key, value)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(pack::unpack::<String>("S", key).unwrap(), "a");
+        assert_eq!(pack::unpack::<String>("S", value).unwrap(), "1");
+
+        assert!(reader.seek(lsn).unwrap());
+        assert_eq!(reader.next().unwrap().unwrap().lsn, lsn);
+        reader.close().unwrap();
+    }
+
+    #[test]
+    fn test_log_records() {
+        let home = "target/wt_log_records";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create,log=(enabled=true)").unwrap();
+        let session = conn.open_session("").unwrap();
+        session.log_printf("hello log").unwrap();
+        session.checkpoint(&CheckpointConfig::new()).unwrap();
+
+        let records = session
+            .open_log_reader()
+            .unwrap()
+            .map(|entry| entry.map(|entry| entry.record))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(records.contains(&LogRecord::Message("hello log".to_string())));
+        assert!(records
+            .iter()
+            .any(|record| matches!(record, LogRecord::Checkpoint { lsn } if lsn.file > 0)));
+    }
+}
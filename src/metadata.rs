@@ -0,0 +1,299 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Descriptors of the objects in the `metadata:` cursor.
+
+use crate::config::{Config, ConfigValue};
+use crate::error::Result;
+
+/// A table, e.g. `table:name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableInfo {
+    pub name: String,
+    pub key_format: String,
+    pub value_format: String,
+    /// Names of the key columns followed by the value columns, if the table
+    /// has named columns.
+    pub columns: Vec<String>,
+    /// Names of the column groups, if the table has named column groups.
+    pub colgroups: Vec<String>,
+    pub config: Config,
+}
+
+/// A column group of a table, e.g. `colgroup:table:name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColgroupInfo {
+    pub table: String,
+    /// The name of the column group, `None` for the column group of a table
+    /// without named column groups.
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    /// The data source storing the column group, e.g. `file:name.wt`.
+    pub source: String,
+    pub config: Config,
+}
+
+/// An index of a table, e.g. `index:table:name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexInfo {
+    pub table: String,
+    pub name: String,
+    pub key_format: String,
+    pub value_format: String,
+    pub columns: Vec<String>,
+    /// The data source storing the index, e.g. `file:name.wti`.
+    pub source: String,
+    pub config: Config,
+}
+
+/// A file, e.g. `file:name.wt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInfo {
+    pub name: String,
+    /// The ID of the file in the log.
+    pub id: Option<u32>,
+    pub key_format: String,
+    pub value_format: String,
+    pub config: Config,
+}
+
+/// An object of the database.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Table(TableInfo),
+    Colgroup(ColgroupInfo),
+    Index(IndexInfo),
+    File(FileInfo),
+    /// An object of another type, e.g. `system:` or `lsm:` objects.
+    Other {
+        uri: String,
+        config: Config,
+    },
+}
+
+/// The text of an identifier or a string, or the rendered value otherwise.
+fn text(value: &ConfigValue) -> String {
+    match value.as_str() {
+        Some(s) => s.to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// The text of `key`, or an empty string if it is not set.
+fn value_of(config: &Config, key: &str) -> String {
+    config.get(key).map(text).unwrap_or_default()
+}
+
+/// The names listed by `key`, e.g. `columns=(a,b)`.
+fn names(config: &Config, key: &str) -> Vec<String> {
+    config
+        .get(key)
+        .and_then(ConfigValue::as_list)
+        .map(|list| list.iter().map(text).collect())
+        .unwrap_or_default()
+}
+
+/// Split `table:name` into `table` and `name`, or return the whole name if it
+/// has no separator.
+fn split_name(name: &str) -> (&str, Option<&str>) {
+    match name.find(':') {
+        Some(i) => (&name[..i], Some(&name[i + 1..])),
+        None => (name, None),
+    }
+}
+
+impl Object {
+    /// Describe the object `uri` from its metadata.
+    pub fn parse(uri: &str, metadata: &str) -> Result<Self> {
+        let config: Config = metadata.parse()?;
+        let (kind, name) = split_name(uri);
+        let name = name.unwrap_or("");
+        let object = match kind {
+            "table" => Object::Table(TableInfo {
+                name: name.to_string(),
+                key_format: value_of(&config, "key_format"),
+                value_format: value_of(&config, "value_format"),
+                columns: names(&config, "columns"),
+                colgroups: names(&config, "colgroups"),
+                config,
+            }),
+            "colgroup" => {
+                let (table, colgroup) = split_name(name);
+                Object::Colgroup(ColgroupInfo {
+                    table: table.to_string(),
+                    name: colgroup.map(String::from),
+                    columns: names(&config, "columns"),
+                    source: value_of(&config, "source"),
+                    config,
+                })
+            }
+            "index" => {
+                let (table, index) = split_name(name);
+                Object::Index(IndexInfo {
+                    table: table.to_string(),
+                    name: index.unwrap_or("").to_string(),
+                    key_format: value_of(&config, "key_format"),
+                    value_format: value_of(&config, "value_format"),
+                    columns: names(&config, "columns"),
+                    source: value_of(&config, "source"),
+                    config,
+                })
+            }
+            "file" => Object::File(FileInfo {
+                name: name.to_string(),
+                id: config
+                    .get("id")
+                    .and_then(ConfigValue::as_int)
+                    .map(|id| id as u32),
+                key_format: value_of(&config, "key_format"),
+                value_format: value_of(&config, "value_format"),
+                config,
+            }),
+            _ => Object::Other {
+                uri: uri.to_string(),
+                config,
+            },
+        };
+        Ok(object)
+    }
+}
+
+/// A table with its column groups and indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub table: TableInfo,
+    pub colgroups: Vec<ColgroupInfo>,
+    pub indices: Vec<IndexInfo>,
+}
+
+/// The tables and files of a database.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub tables: Vec<TableSchema>,
+    /// All files, including the ones storing tables and indices.
+    pub files: Vec<FileInfo>,
+}
+
+impl Schema {
+    /// Group `objects` by table. Column groups and indices of missing tables
+    /// are ignored.
+    pub fn new(objects: Vec<Object>) -> Self {
+        let mut schema = Schema::default();
+        let mut colgroups = Vec::new();
+        let mut indices = Vec::new();
+        for object in objects {
+            match object {
+                Object::Table(table) => schema.tables.push(TableSchema {
+                    table,
+                    colgroups: Vec::new(),
+                    indices: Vec::new(),
+                }),
+                Object::Colgroup(colgroup) => colgroups.push(colgroup),
+                Object::Index(index) => indices.push(index),
+                Object::File(file) => schema.files.push(file),
+                Object::Other { .. } => (),
+            }
+        }
+        for colgroup in colgroups {
+            if let Some(table) = schema.table_mut(&colgroup.table) {
+                table.colgroups.push(colgroup);
+            }
+        }
+        for index in indices {
+            if let Some(table) = schema.table_mut(&index.table) {
+                table.indices.push(index);
+            }
+        }
+        schema
+    }
+
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.iter().find(|t| t.table.name == name)
+    }
+
+    fn table_mut(&mut self, name: &str) -> Option<&mut TableSchema> {
+        self.tables.iter_mut().find(|t| t.table.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IndexConfig;
+    use crate::Connection;
+    use test_utils;
+
+    #[test]
+    fn test_parse_object() {
+        let object = Object::parse(
+            "table:people",
+            "colgroups=(main),columns=(id,name,age),key_format=Q,value_format=SI",
+        )
+        .unwrap();
+        match object {
+            Object::Table(table) => {
+                assert_eq!(table.name, "people");
+                assert_eq!(table.key_format, "Q");
+                assert_eq!(table.value_format, "SI");
+                assert_eq!(table.columns, vec!["id", "name", "age"]);
+                assert_eq!(table.colgroups, vec!["main"]);
+            }
+            object => panic!("unexpected object {:?}", object),
+        }
+
+        let object =
+            Object::parse("colgroup:people", "columns=,source=\"file:people.wt\"").unwrap();
+        match object {
+            Object::Colgroup(colgroup) => {
+                assert_eq!(colgroup.table, "people");
+                assert_eq!(colgroup.name, None);
+                assert_eq!(colgroup.source, "file:people.wt");
+            }
+            object => panic!("unexpected object {:?}", object),
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let home = "target/wt_schema";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create(
+                "table:people",
+                "key_format=Q,value_format=SI,columns=(id,name,age)",
+            )
+            .unwrap();
+        let config = IndexConfig::new().columns(&["name"]);
+        session.create_index("people", "by_name", &config).unwrap();
+
+        let objects = session.list_objects().unwrap();
+        assert!(objects.iter().any(|o| match o {
+            Object::Index(index) => index.name == "by_name",
+            _ => false,
+        }));
+
+        let schema = conn.schema().unwrap();
+        let people = schema.table("people").unwrap();
+        assert_eq!(people.table.key_format, "Q");
+        assert_eq!(people.table.value_format, "SI");
+        assert_eq!(people.table.columns, vec!["id", "name", "age"]);
+        assert_eq!(people.colgroups.len(), 1);
+        assert_eq!(people.indices.len(), 1);
+        assert_eq!(people.indices[0].name, "by_name");
+        assert_eq!(people.indices[0].columns[0], "name");
+        assert!(schema.files.iter().any(|f| f.name == "people.wt"));
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export statistics in the OpenMetrics text format.
+//!
+//! Connection statistics are named `wiredtiger_connection_<name>` and data
+//! source statistics `wiredtiger_data_source_<name>` with a `uri` label, where
+//! `<name>` is the name of the statistic, e.g. `cache_bytes_inuse`.
+//!
+//! WiredTiger does not say which statistics are cumulative, so statistics are
+//! typed by their names and descriptions: the ones describing a current state
+//! or a limit, like `cache: bytes currently in the cache`, are gauges and the
+//! others are counters.
+
+use crate::error::Result;
+use crate::statistics::{ConnectionStat, DataSourceStat, Stat, StatId, Statistics};
+use crate::Connection;
+use std::fmt::Write;
+use std::mem::ManuallyDrop;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use wiredtiger_sys::WT_CONNECTION;
+
+/// The type of a metric.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+}
+
+impl MetricType {
+    /// The type of the statistic `name` described by `description`.
+    pub fn of(name: &str, description: &str) -> Self {
+        const GAUGE_SUFFIXES: [&str; 4] = ["_inuse", "_max", "_min", "_recent"];
+        const GAUGE_WORDS: [&str; 6] = [
+            "currently",
+            "current ",
+            "maximum",
+            "minimum",
+            "configured",
+            "most recent",
+        ];
+        let description = description.to_lowercase();
+        if GAUGE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+            || GAUGE_WORDS.iter().any(|word| description.contains(word))
+        {
+            MetricType::Gauge
+        } else {
+            MetricType::Counter
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+/// Escape a help text or a label value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('"', "\\\"")
+}
+
+/// Write the family of the statistic `id`, with one sample per label set.
+fn write_family<'a, S, I>(out: &mut String, prefix: &str, id: S, samples: I)
+where
+    S: StatId,
+    I: IntoIterator<Item = (Option<&'a str>, &'a Stat)>,
+{
+    let mut samples = samples.into_iter().peekable();
+    let description = match samples.peek() {
+        Some((_, stat)) => stat.description.clone(),
+        None => return,
+    };
+    let name = format!("{}_{}", prefix, id.name());
+    let metric_type = MetricType::of(id.name(), &description);
+    let suffix = match metric_type {
+        MetricType::Counter => "_total",
+        MetricType::Gauge => "",
+    };
+    writeln!(out, "# TYPE {} {}", name, metric_type.as_str()).unwrap();
+    writeln!(out, "# HELP {} {}", name, escape(&description)).unwrap();
+    for (uri, stat) in samples {
+        match uri {
+            Some(uri) => writeln!(
+                out,
+                "{}{}{{uri=\"{}\"}} {}",
+                name,
+                suffix,
+                escape(uri),
+                stat.value
+            ),
+            None => writeln!(out, "{}{} {}", name, suffix, stat.value),
+        }
+        .unwrap();
+    }
+}
+
+/// Render connection statistics and the statistics of some data sources,
+/// keyed by their URIs.
+pub fn render(
+    connection: &Statistics<ConnectionStat>,
+    data_sources: &[(String, Statistics<DataSourceStat>)],
+) -> String {
+    let mut out = String::new();
+    for (id, stat) in connection.iter() {
+        write_family(&mut out, "wiredtiger_connection", id, Some((None, stat)));
+    }
+
+    let mut ids: Vec<DataSourceStat> = data_sources
+        .iter()
+        .flat_map(|(_, stats)| stats.iter().map(|(id, _)| id))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    for id in ids {
+        let samples = data_sources
+            .iter()
+            .filter_map(|(uri, stats)| stats.get(id).map(|stat| (Some(uri.as_str()), stat)));
+        write_family(&mut out, "wiredtiger_data_source", id, samples);
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+/// Collects the statistics of a connection and some of its data sources.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsExporter {
+    data_sources: Vec<String>,
+}
+
+impl MetricsExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also export the statistics of the data source `uri`, e.g. `table:name`.
+    pub fn data_source<U: Into<String>>(mut self, uri: U) -> Self {
+        self.data_sources.push(uri.into());
+        self
+    }
+
+    /// Read the statistics of `conn` and render them.
+    pub fn export(&self, conn: &Connection) -> Result<String> {
+        let session = conn.open_session("")?;
+        let mut data_sources = Vec::with_capacity(self.data_sources.len());
+        for uri in &self.data_sources {
+            data_sources.push((uri.clone(), session.statistics(uri)?));
+        }
+        Ok(render(&conn.statistics()?, &data_sources))
+    }
+}
+
+struct ConnectionPtr(*mut WT_CONNECTION);
+
+unsafe impl Send for ConnectionPtr {}
+
+/// A thread exporting the statistics of a connection periodically.
+pub(crate) struct MetricsSampler {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+    latest: Arc<Mutex<Option<String>>>,
+}
+
+impl MetricsSampler {
+    /// Start sampling `conn`, which must outlive the sampler.
+    pub(crate) fn start(
+        conn: *mut WT_CONNECTION,
+        exporter: MetricsExporter,
+        interval: Duration,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let latest = Arc::new(Mutex::new(None));
+        let conn = ConnectionPtr(conn);
+        let thread = {
+            let latest = latest.clone();
+            thread::spawn(move || {
+                // The connection is owned by the caller, which stops the
+                // sampler before closing it.
+                let conn = ManuallyDrop::new(unsafe { Connection::new_unchecked(conn.0) });
+                loop {
+                    match exporter.export(&conn) {
+                        Ok(text) => *latest.lock().unwrap() = Some(text),
+                        Err(error) => eprintln!("error happened when export metrics: {}", error),
+                    }
+                    match stopped.recv_timeout(interval) {
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        _ => break,
+                    }
+                }
+            })
+        };
+        Self {
+            stop,
+            thread,
+            latest,
+        }
+    }
+
+    /// The statistics of the last successful sample.
+    pub(crate) fn latest(&self) -> Option<String> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    pub(crate) fn stop(self) {
+        let _ = self.stop.send(());
+        if self.thread.join().is_err() {
+            eprintln!("error happened when stop metrics sampler: thread panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils;
+
+    fn stat(description: &str, value: i64) -> Stat {
+        Stat {
+            description: description.to_string(),
+            value,
+            printable: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_metric_type() {
+        assert_eq!(
+            MetricType::of("cache_bytes_inuse", "cache: bytes currently in the cache"),
+            MetricType::Gauge
+        );
+        assert_eq!(
+            MetricType::of("cache_bytes_max", "cache: maximum bytes configured"),
+            MetricType::Gauge
+        );
+        assert_eq!(
+            MetricType::of("cursor_insert", "cursor: insert calls"),
+            MetricType::Counter
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let connection: Statistics<ConnectionStat> = vec![
+            (
+                ConnectionStat::CacheBytesInuse,
+                stat("cache: bytes currently in the cache", 10),
+            ),
+            (
+                ConnectionStat::CacheEvictionFail,
+                stat("cache: pages selected for eviction unable to be evicted", 2),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let table: Statistics<DataSourceStat> = vec![(
+            DataSourceStat::CursorInsert,
+            stat("cursor: insert calls", 3),
+        )]
+        .into_iter()
+        .collect();
+        let data_sources = vec![
+            ("table:a".to_string(), table.clone()),
+            ("table:\"b\"".to_string(), table),
+        ];
+
+        let text = render(&connection, &data_sources);
+        let expected = "\
+# TYPE wiredtiger_connection_cache_bytes_inuse gauge
+# HELP wiredtiger_connection_cache_bytes_inuse cache: bytes currently in the cache
+wiredtiger_connection_cache_bytes_inuse 10
+# TYPE wiredtiger_connection_cache_eviction_fail counter
+# HELP wiredtiger_connection_cache_eviction_fail cache: pages selected for eviction unable to be evicted
+wiredtiger_connection_cache_eviction_fail_total 2
+# TYPE wiredtiger_data_source_cursor_insert counter
+# HELP wiredtiger_data_source_cursor_insert cursor: insert calls
+wiredtiger_data_source_cursor_insert_total{uri=\"table:a\"} 3
+wiredtiger_data_source_cursor_insert_total{uri=\"table:\\\"b\\\"\"} 3
+# EOF
+";
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_sampler() {
+        let home = "target/wt_metrics_sampler";
+        test_utils::make_work_dir(home, true);
+        let mut conn = Connection::open(home, "create,statistics=(fast)").unwrap();
+        {
+            let session = conn.open_session("").unwrap();
+            session
+                .create("table:metrics", "key_format=S,value_format=S")
+                .unwrap();
+        }
+
+        let exporter = MetricsExporter::new().data_source("table:metrics");
+        let text = exporter.export(&conn).unwrap();
+        assert!(text.contains("wiredtiger_connection_cache_bytes_max "));
+        assert!(text.ends_with("# EOF\n"));
+
+        conn.start_metrics_sampler(exporter, Duration::from_millis(10));
+        let mut latest = None;
+        for _ in 0..100 {
+            latest = conn.metrics();
+            if latest.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(latest
+            .unwrap()
+            .contains("wiredtiger_data_source_cursor_insert_total{uri=\"table:metrics\"}"));
+        conn.close("").unwrap();
+    }
+}
@@ -0,0 +1,485 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pack and unpack Rust values using WiredTiger's format strings.
+//!
+//! Values are converted with WiredTiger's packing streams, so the encoding is
+//! exactly the one `wiredtiger_struct_pack` and `wiredtiger_struct_unpack`
+//! produce for the same format.
+//!
+//! | Format            | Rust types                          |
+//! |-------------------|-------------------------------------|
+//! | `b` `h` `i` `l` `q` | `i8` `i16` `i32` `i64`            |
+//! | `B` `H` `I` `L` `Q` `r` `t` | `u8` `u16` `u32` `u64`    |
+//! | `S` `s`           | `str`, `String`                     |
+//! | `u` `U`           | `[u8]`, `Vec<u8>`                   |
+//!
+//! Composite formats such as `SiQ` map to tuples, e.g. `(String, i32, u64)`.
+
+use crate::error::{Error, Result, EINVAL, ENOMEM};
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use wiredtiger_sys::{
+    wiredtiger_pack_close, wiredtiger_pack_int, wiredtiger_pack_item, wiredtiger_pack_start,
+    wiredtiger_pack_str, wiredtiger_pack_uint, wiredtiger_unpack_int, wiredtiger_unpack_item,
+    wiredtiger_unpack_start, wiredtiger_unpack_str, wiredtiger_unpack_uint, WT_ITEM,
+    WT_PACK_STREAM, WT_SESSION,
+};
+
+/// A value that can be packed into WiredTiger's format.
+pub trait Pack {
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>);
+}
+
+/// A value that can be unpacked from WiredTiger's format.
+pub trait Unpack: Sized {
+    fn unpack(unpacker: &mut Unpacker<'_>) -> Result<Self>;
+}
+
+/// A value with a default WiredTiger format, used to derive the formats of
+/// records.
+pub trait Format {
+    const FORMAT: &'static str;
+}
+
+enum Field<'a> {
+    Int(i64),
+    UInt(u64),
+    Str(CString),
+    Item(&'a [u8]),
+}
+
+impl<'a> Field<'a> {
+    /// The maximum number of bytes the field takes once packed.
+    fn max_size(&self) -> usize {
+        // A packed integer takes at most 9 bytes.
+        match self {
+            Field::Int(_) | Field::UInt(_) => 9,
+            Field::Str(s) => s.as_bytes_with_nul().len(),
+            Field::Item(item) => item.len() + 9,
+        }
+    }
+}
+
+/// Collects the fields of a value to be packed.
+pub struct Packer<'a> {
+    fields: Vec<Field<'a>>,
+    // Why the first field that cannot be packed was rejected.
+    error: Option<String>,
+}
+
+impl<'a> Packer<'a> {
+    fn new() -> Self {
+        Self {
+            fields: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[inline]
+    pub fn pack_int(&mut self, i: i64) {
+        self.fields.push(Field::Int(i));
+    }
+
+    #[inline]
+    pub fn pack_uint(&mut self, u: u64) {
+        self.fields.push(Field::UInt(u));
+    }
+
+    /// Pack a string. Packing fails with `Error::Codec` if `s` contains a nul
+    /// byte.
+    #[inline]
+    pub fn pack_str(&mut self, s: &str) {
+        match CString::new(s.as_bytes()) {
+            Ok(s) => self.fields.push(Field::Str(s)),
+            Err(_) => {
+                self.error
+                    .get_or_insert_with(|| "string contains a nul byte".to_string());
+            }
+        }
+    }
+
+    #[inline]
+    pub fn pack_item(&mut self, item: &'a [u8]) {
+        self.fields.push(Field::Item(item));
+    }
+
+    unsafe fn finish(&self, session: *mut WT_SESSION, format: &CStr) -> Result<Vec<u8>> {
+        if let Some(error) = &self.error {
+            return Err(Error::Codec(error.clone()));
+        }
+        let mut size = self
+            .fields
+            .iter()
+            .map(Field::max_size)
+            .sum::<usize>()
+            .max(1);
+        loop {
+            match self.pack_into(session, format, size) {
+                // Fixed-size fields like `10s` may be larger than the estimate.
+                Err(Error::System(ENOMEM)) => size *= 2,
+                result => return result,
+            }
+        }
+    }
+
+    unsafe fn pack_into(
+        &self,
+        session: *mut WT_SESSION,
+        format: &CStr,
+        size: usize,
+    ) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; size];
+        let mut stream: *mut WT_PACK_STREAM = ptr::null_mut();
+        wt_try!(wiredtiger_pack_start(
+            session,
+            format.as_ptr(),
+            buffer.as_mut_ptr() as *mut c_void,
+            size,
+            &mut stream as *mut *mut WT_PACK_STREAM
+        ));
+
+        let mut result = 0;
+        for field in &self.fields {
+            result = match field {
+                Field::Int(i) => wiredtiger_pack_int(stream, *i),
+                Field::UInt(u) => wiredtiger_pack_uint(stream, *u),
+                Field::Str(s) => wiredtiger_pack_str(stream, s.as_ptr()),
+                Field::Item(item) => {
+                    let mut item = new_item(item);
+                    wiredtiger_pack_item(stream, &mut item as *mut WT_ITEM)
+                }
+            };
+            if result != 0 {
+                break;
+            }
+        }
+
+        let mut used = 0usize;
+        let close_result = wiredtiger_pack_close(stream, &mut used as *mut usize);
+        wt_try!(result);
+        wt_try!(close_result);
+
+        buffer.truncate(used);
+        Ok(buffer)
+    }
+}
+
+/// Reads the fields of a packed value.
+pub struct Unpacker<'a> {
+    stream: *mut WT_PACK_STREAM,
+    buffer: &'a [u8],
+}
+
+impl<'a> Unpacker<'a> {
+    unsafe fn new(session: *mut WT_SESSION, format: &CStr, buffer: &'a [u8]) -> Result<Self> {
+        let mut stream: *mut WT_PACK_STREAM = ptr::null_mut();
+        wt_try!(wiredtiger_unpack_start(
+            session,
+            format.as_ptr(),
+            buffer.as_ptr() as *const c_void,
+            buffer.len(),
+            &mut stream as *mut *mut WT_PACK_STREAM
+        ));
+        Ok(Self { stream, buffer })
+    }
+
+    pub fn unpack_int(&mut self) -> Result<i64> {
+        let mut i = 0i64;
+        unsafe {
+            wt_try!(wiredtiger_unpack_int(self.stream, &mut i as *mut i64));
+        }
+        Ok(i)
+    }
+
+    pub fn unpack_uint(&mut self) -> Result<u64> {
+        let mut u = 0u64;
+        unsafe {
+            wt_try!(wiredtiger_unpack_uint(self.stream, &mut u as *mut u64));
+        }
+        Ok(u)
+    }
+
+    /// Unpack a string, which is borrowed from the packed buffer.
+    ///
+    /// Returns `Error::System(EINVAL)` if the string is not valid UTF-8.
+    pub fn unpack_str(&mut self) -> Result<&'a str> {
+        let mut s: *const c_char = ptr::null();
+        unsafe {
+            wt_try!(wiredtiger_unpack_str(
+                self.stream,
+                &mut s as *mut *const c_char
+            ));
+        }
+
+        // Fixed-size strings are not nul-terminated when they fill the field,
+        // so never look past the end of the buffer.
+        let offset = s as usize - self.buffer.as_ptr() as usize;
+        debug_assert!(offset <= self.buffer.len());
+        let bytes = &self.buffer[offset..];
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        std::str::from_utf8(&bytes[..len]).map_err(|_| Error::System(EINVAL))
+    }
+
+    /// Unpack a byte array, which is borrowed from the packed buffer.
+    pub fn unpack_item(&mut self) -> Result<&'a [u8]> {
+        let mut item = new_item(&[]);
+        unsafe {
+            wt_try!(wiredtiger_unpack_item(
+                self.stream,
+                &mut item as *mut WT_ITEM
+            ));
+            Ok(item_as_slice(&item))
+        }
+    }
+
+    /// Close the stream, returning the number of bytes unpacked.
+    fn close(self) -> Result<usize> {
+        let stream = self.stream;
+        std::mem::forget(self);
+        let mut used = 0usize;
+        unsafe {
+            wt_try!(wiredtiger_pack_close(stream, &mut used as *mut usize));
+        }
+        Ok(used)
+    }
+}
+
+impl<'a> Drop for Unpacker<'a> {
+    fn drop(&mut self) {
+        let mut used = 0usize;
+        unsafe {
+            wiredtiger_pack_close(self.stream, &mut used as *mut usize);
+        }
+    }
+}
+
+/// Pack `value` with the WiredTiger `format`.
+pub fn pack<T: Pack + ?Sized>(format: &str, value: &T) -> Result<Vec<u8>> {
+    let c_format = CString::new(format.as_bytes()).unwrap();
+    unsafe { pack_with(ptr::null_mut(), &c_format, value) }
+}
+
+/// Unpack a value from `buffer` with the WiredTiger `format`.
+pub fn unpack<T: Unpack>(format: &str, buffer: &[u8]) -> Result<T> {
+    let c_format = CString::new(format.as_bytes()).unwrap();
+    unsafe { unpack_with(ptr::null_mut(), &c_format, buffer) }
+}
+
+pub(crate) unsafe fn pack_with<T: Pack + ?Sized>(
+    session: *mut WT_SESSION,
+    format: &CStr,
+    value: &T,
+) -> Result<Vec<u8>> {
+    let mut packer = Packer::new();
+    value.pack(&mut packer);
+    packer.finish(session, format)
+}
+
+pub(crate) unsafe fn unpack_with<T: Unpack>(
+    session: *mut WT_SESSION,
+    format: &CStr,
+    buffer: &[u8],
+) -> Result<T> {
+    let mut unpacker = Unpacker::new(session, format, buffer)?;
+    T::unpack(&mut unpacker)
+}
+
+/// Like `unpack_with`, but also returns the number of bytes the value takes,
+/// which is less than the buffer length if fields follow the ones of `format`.
+pub(crate) unsafe fn unpack_prefix_with<T: Unpack>(
+    session: *mut WT_SESSION,
+    format: &CStr,
+    buffer: &[u8],
+) -> Result<(T, usize)> {
+    let mut unpacker = Unpacker::new(session, format, buffer)?;
+    let value = T::unpack(&mut unpacker)?;
+    Ok((value, unpacker.close()?))
+}
+
+/// Make a `WT_ITEM` referring to `data`.
+pub(crate) fn new_item(data: &[u8]) -> WT_ITEM {
+    let mut item: WT_ITEM = unsafe { std::mem::zeroed() };
+    item.data = data.as_ptr() as *const c_void;
+    item.size = data.len();
+    item
+}
+
+/// View the data of a `WT_ITEM` as a slice.
+///
+/// The caller chooses the lifetime, which must not outlive the item's buffer.
+pub(crate) unsafe fn item_as_slice<'a>(item: &WT_ITEM) -> &'a [u8] {
+    if item.size == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(item.data as *const u8, item.size)
+    }
+}
+
+impl<T: Pack + ?Sized> Pack for &T {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        (**self).pack(packer)
+    }
+}
+
+macro_rules! impl_int {
+    ($($ty: ty => $format: expr, $pack: ident, $unpack: ident;)*) => {
+        $(
+            impl Format for $ty {
+                const FORMAT: &'static str = $format;
+            }
+
+            impl Pack for $ty {
+                #[inline]
+                fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+                    packer.$pack((*self).into());
+                }
+            }
+
+            impl Unpack for $ty {
+                #[inline]
+                fn unpack(unpacker: &mut Unpacker<'_>) -> Result<Self> {
+                    let v = unpacker.$unpack()?;
+                    <$ty>::try_from(v).map_err(|_| Error::System(EINVAL))
+                }
+            }
+        )*
+    };
+}
+
+impl_int! {
+    i8 => "b", pack_int, unpack_int;
+    i16 => "h", pack_int, unpack_int;
+    i32 => "i", pack_int, unpack_int;
+    i64 => "q", pack_int, unpack_int;
+    u8 => "B", pack_uint, unpack_uint;
+    u16 => "H", pack_uint, unpack_uint;
+    u32 => "I", pack_uint, unpack_uint;
+    u64 => "Q", pack_uint, unpack_uint;
+}
+
+impl Pack for str {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        packer.pack_str(self);
+    }
+}
+
+impl Pack for String {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        packer.pack_str(self);
+    }
+}
+
+impl Format for String {
+    const FORMAT: &'static str = "S";
+}
+
+impl Unpack for String {
+    #[inline]
+    fn unpack(unpacker: &mut Unpacker<'_>) -> Result<Self> {
+        unpacker.unpack_str().map(String::from)
+    }
+}
+
+impl Pack for [u8] {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        packer.pack_item(self);
+    }
+}
+
+impl Pack for Vec<u8> {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        packer.pack_item(self);
+    }
+}
+
+impl Format for Vec<u8> {
+    const FORMAT: &'static str = "u";
+}
+
+impl Unpack for Vec<u8> {
+    #[inline]
+    fn unpack(unpacker: &mut Unpacker<'_>) -> Result<Self> {
+        unpacker.unpack_item().map(<[u8]>::to_vec)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name: ident)+) => {
+        impl<$($name: Pack),+> Pack for ($($name,)+) {
+            #[inline]
+            #[allow(non_snake_case)]
+            fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+                let ($($name,)+) = self;
+                $($name.pack(packer);)+
+            }
+        }
+
+        impl<$($name: Unpack),+> Unpack for ($($name,)+) {
+            #[inline]
+            fn unpack(unpacker: &mut Unpacker<'_>) -> Result<Self> {
+                Ok(($($name::unpack(unpacker)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A B);
+impl_tuple!(A B C);
+impl_tuple!(A B C D);
+impl_tuple!(A B C D E);
+impl_tuple!(A B C D E F);
+impl_tuple!(A B C D E F G);
+impl_tuple!(A B C D E F G H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let packed = pack("SiQ", &("hello", -3i32, 42u64)).unwrap();
+        let (s, i, q): (String, i32, u64) = unpack("SiQ", &packed).unwrap();
+        assert_eq!(s, "hello");
+        assert_eq!(i, -3);
+        assert_eq!(q, 42);
+
+        let packed = pack("uS", &(vec![1u8, 2, 3], "world")).unwrap();
+        let (u, s): (Vec<u8>, String) = unpack("uS", &packed).unwrap();
+        assert_eq!(u, vec![1, 2, 3]);
+        assert_eq!(s, "world");
+    }
+
+    #[test]
+    fn test_pack_fixed_size() {
+        let packed = pack("20s", "abc").unwrap();
+        assert_eq!(packed.len(), 20);
+        let s: String = unpack("20s", &packed).unwrap();
+        assert_eq!(s, "abc");
+    }
+
+    #[test]
+    fn test_pack_mismatch() {
+        assert!(pack("S", &1i64).is_err());
+        assert!(matches!(pack("S", "a\0b"), Err(Error::Codec(_))));
+    }
+}
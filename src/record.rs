@@ -0,0 +1,433 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tables of Rust structs.
+//!
+//! A `Record` maps the fields of a struct to the columns of a table, and is
+//! usually derived with `#[derive(WtRecord)]` (the `derive` feature):
+//!
+//! ```ignore
+//! #[derive(WtRecord)]
+//! struct User {
+//!     #[wt(key)]
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! let users = Table::<User>::create(&session)?;
+//! users.insert(&User { id: 1, name: "alice".to_string() })?;
+//! let user = users.get(&(1,))?;
+//! ```
+//!
+//! The key and the value are tuples, so they hold at most 8 columns each.
+
+use crate::config::{ColgroupConfig, IndexConfig, TableConfig};
+use crate::error::Result;
+use crate::index::Index;
+use crate::pack::{Pack, Packer, Unpack};
+use crate::{uri, Cursor, Iter, Session};
+use std::marker::PhantomData;
+
+/// A struct stored as a row of the table `table:<TABLE>`.
+pub trait Record: Sized {
+    /// The key columns, as a tuple.
+    type Key: Pack + Unpack;
+    /// The value columns, as a tuple.
+    type Value: Unpack;
+
+    const TABLE: &'static str;
+    const KEY_COLUMNS: &'static [&'static str];
+    const VALUE_COLUMNS: &'static [&'static str];
+    /// The column groups of the table and their value columns. A table
+    /// without column groups stores all columns in one file.
+    const COLGROUPS: &'static [(&'static str, &'static [&'static str])] = &[];
+
+    fn key_format() -> String;
+    fn value_format() -> String;
+
+    fn pack_key<'a>(&'a self, packer: &mut Packer<'a>);
+    fn pack_value<'a>(&'a self, packer: &mut Packer<'a>);
+
+    fn from_parts(key: Self::Key, value: Self::Value) -> Self;
+
+    /// The URI of the table.
+    fn uri() -> String {
+        uri::table(Self::TABLE)
+    }
+
+    /// The configuration to create the table with.
+    fn table_config() -> TableConfig {
+        let columns: Vec<&str> = Self::KEY_COLUMNS
+            .iter()
+            .chain(Self::VALUE_COLUMNS)
+            .copied()
+            .collect();
+        let config = TableConfig::new()
+            .key_format(&Self::key_format())
+            .value_format(&Self::value_format())
+            .columns(&columns);
+        if Self::COLGROUPS.is_empty() {
+            return config;
+        }
+        let colgroups: Vec<&str> = Self::COLGROUPS.iter().map(|(name, _)| *name).collect();
+        config.colgroups(&colgroups)
+    }
+}
+
+struct KeyOf<'r, R>(&'r R);
+
+impl<'r, R: Record> Pack for KeyOf<'r, R> {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        self.0.pack_key(packer)
+    }
+}
+
+struct ValueOf<'r, R>(&'r R);
+
+impl<'r, R: Record> Pack for ValueOf<'r, R> {
+    #[inline]
+    fn pack<'a>(&'a self, packer: &mut Packer<'a>) {
+        self.0.pack_value(packer)
+    }
+}
+
+/// The table of the `R` records.
+pub struct Table<'s, R> {
+    session: &'s Session<'s>,
+    uri: String,
+    record: PhantomData<fn() -> R>,
+}
+
+impl<'s, R: Record> Table<'s, R> {
+    /// Create the table and its column groups if they do not exist, and open
+    /// the table.
+    pub fn create(session: &'s Session<'s>) -> Result<Self> {
+        let table = Self::open(session);
+        session.create(&table.uri, R::table_config().to_string())?;
+        for (name, columns) in R::COLGROUPS {
+            let config = ColgroupConfig::new().columns(columns);
+            session.create_colgroup(R::TABLE, name, &config)?;
+        }
+        Ok(table)
+    }
+
+    /// Open the table without creating it.
+    pub fn open(session: &'s Session<'s>) -> Self {
+        Self {
+            session,
+            uri: R::uri(),
+            record: PhantomData,
+        }
+    }
+
+    /// The URI of the table.
+    #[inline]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Open a cursor on the table.
+    pub fn cursor(&self) -> Result<RecordCursor<'_, R>> {
+        self.cursor_with("")
+    }
+
+    /// Open a cursor on the table with a cursor configuration.
+    pub fn cursor_with<C: AsRef<str>>(&self, config: C) -> Result<RecordCursor<'_, R>> {
+        Ok(RecordCursor::new(
+            self.session.open_cursor(&self.uri, config)?,
+        ))
+    }
+
+    /// Open a cursor reading only the `columns` of the records, decoded as
+    /// `V`.
+    pub fn projection<V: Unpack, S: AsRef<str>>(
+        &self,
+        columns: &[S],
+    ) -> Result<Projection<'_, R, V>> {
+        Ok(Projection {
+            cursor: self.session.open_projection(R::TABLE, columns, "")?,
+            marker: PhantomData,
+        })
+    }
+
+    /// Insert or overwrite `record`.
+    pub fn insert(&self, record: &R) -> Result<()> {
+        self.cursor()?.insert(record)
+    }
+
+    /// Get the record of `key`.
+    pub fn get(&self, key: &R::Key) -> Result<Option<R>> {
+        self.cursor()?.get(key)
+    }
+
+    /// Remove the record of `key`. Returns `false` if it did not exist.
+    pub fn remove(&self, key: &R::Key) -> Result<bool> {
+        self.cursor()?.remove(key)
+    }
+
+    /// Create the index `name` on `columns` if it does not exist, and open
+    /// it.
+    pub fn create_index<S: AsRef<str>>(&self, name: &str, columns: &[S]) -> Result<Index<'s, R>> {
+        let config = IndexConfig::new().columns(columns);
+        self.session.create_index(R::TABLE, name, &config)?;
+        Ok(self.index(name))
+    }
+
+    /// Open the index `name` without creating it.
+    pub fn index(&self, name: &str) -> Index<'s, R> {
+        Index::new(self.session, name)
+    }
+}
+
+/// A cursor that reads and writes `R` records.
+pub struct RecordCursor<'s, R> {
+    cursor: Cursor<'s>,
+    record: PhantomData<fn() -> R>,
+}
+
+impl<'s, R: Record> RecordCursor<'s, R> {
+    /// Wrap a cursor on a table whose columns match `R`.
+    pub fn new(cursor: Cursor<'s>) -> Self {
+        Self {
+            cursor,
+            record: PhantomData,
+        }
+    }
+
+    /// The underlying cursor.
+    #[inline]
+    pub fn cursor(&mut self) -> &mut Cursor<'s> {
+        &mut self.cursor
+    }
+
+    /// Insert or overwrite `record`, depending on the `overwrite`
+    /// configuration of the cursor.
+    pub fn insert(&mut self, record: &R) -> Result<()> {
+        self.cursor.set_key(&KeyOf(record))?;
+        self.cursor.set_value(&ValueOf(record))?;
+        self.cursor.insert()
+    }
+
+    /// Update the existing record with the key of `record`.
+    pub fn update(&mut self, record: &R) -> Result<()> {
+        self.cursor.set_key(&KeyOf(record))?;
+        self.cursor.set_value(&ValueOf(record))?;
+        self.cursor.update()
+    }
+
+    /// Get the record of `key`.
+    pub fn get(&mut self, key: &R::Key) -> Result<Option<R>> {
+        self.cursor.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(None);
+        }
+        let record = self.current()?;
+        self.cursor.reset()?;
+        Ok(Some(record))
+    }
+
+    /// Remove the record of `key`. Returns `false` if it did not exist.
+    pub fn remove(&mut self, key: &R::Key) -> Result<bool> {
+        self.cursor.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(false);
+        }
+        self.cursor.remove()?;
+        Ok(true)
+    }
+
+    /// The record at the current position.
+    pub fn current(&self) -> Result<R> {
+        Ok(R::from_parts(
+            self.cursor.get_key()?,
+            self.cursor.get_value()?,
+        ))
+    }
+
+    /// Iterate over all records in key order.
+    pub fn iter(&mut self) -> Result<RecordIter<'_, 's, R>> {
+        Ok(RecordIter {
+            inner: self.cursor.iter()?,
+        })
+    }
+
+    /// Iterate over all records in reverse key order.
+    pub fn iter_rev(&mut self) -> Result<RecordIter<'_, 's, R>> {
+        Ok(RecordIter {
+            inner: self.cursor.iter_rev()?,
+        })
+    }
+}
+
+/// An iterator over the records of a `RecordCursor`.
+pub struct RecordIter<'c, 's, R: Record> {
+    inner: Iter<'c, 's, R::Key, R::Value>,
+}
+
+impl<'c, 's, R: Record> Iterator for RecordIter<'c, 's, R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|record| record.map(|(key, value)| R::from_parts(key, value)))
+    }
+}
+
+/// A cursor reading some columns of `R` records as `V` values.
+pub struct Projection<'s, R, V> {
+    cursor: Cursor<'s>,
+    marker: PhantomData<fn() -> (R, V)>,
+}
+
+impl<'s, R: Record, V: Unpack> Projection<'s, R, V> {
+    /// The underlying cursor.
+    #[inline]
+    pub fn cursor(&mut self) -> &mut Cursor<'s> {
+        &mut self.cursor
+    }
+
+    /// Get the projected columns of the record of `key`.
+    pub fn get(&mut self, key: &R::Key) -> Result<Option<V>> {
+        self.cursor.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(None);
+        }
+        let value = self.cursor.get_value()?;
+        self.cursor.reset()?;
+        Ok(Some(value))
+    }
+
+    /// Iterate over the keys and projected columns of all records in key
+    /// order.
+    pub fn iter(&mut self) -> Result<Iter<'_, 's, R::Key, V>> {
+        self.cursor.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Connection;
+    use test_utils;
+    use wiredtiger_derive::WtRecord;
+
+    #[derive(Debug, PartialEq, WtRecord)]
+    struct UserRow {
+        #[wt(key)]
+        id: u64,
+        #[wt(column = "user_name")]
+        name: String,
+        age: u32,
+    }
+
+    #[derive(Debug, PartialEq, WtRecord)]
+    struct Document {
+        #[wt(key)]
+        id: u64,
+        #[wt(colgroup = "hot")]
+        title: String,
+        #[wt(colgroup = "cold")]
+        body: String,
+        #[wt(colgroup = "hot")]
+        views: u64,
+    }
+
+    #[test]
+    fn test_derive() {
+        assert_eq!(UserRow::uri(), "table:user_row");
+        assert_eq!(UserRow::key_format(), "Q");
+        assert_eq!(UserRow::value_format(), "SI");
+        assert_eq!(
+            UserRow::table_config().to_string(),
+            "key_format=Q,value_format=SI,columns=(id,user_name,age)"
+        );
+        assert!(UserRow::COLGROUPS.is_empty());
+        assert_eq!(
+            Document::COLGROUPS,
+            &[("hot", &["title", "views"][..]), ("cold", &["body"][..])]
+        );
+        assert_eq!(
+            Document::table_config().to_string(),
+            "key_format=Q,value_format=SSQ,columns=(id,title,body,views),colgroups=(hot,cold)"
+        );
+    }
+
+    #[test]
+    fn test_colgroups() {
+        let home = "target/wt_record_colgroups";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        let documents = Table::<Document>::create(&session).unwrap();
+
+        let document = Document {
+            id: 1,
+            title: "hello".to_string(),
+            body: "a long text".to_string(),
+            views: 7,
+        };
+        documents.insert(&document).unwrap();
+        assert_eq!(documents.get(&(1,)).unwrap(), Some(document));
+
+        let mut hot = documents
+            .projection::<(String, u64), _>(&["title", "views"])
+            .unwrap();
+        assert_eq!(hot.get(&(1,)).unwrap(), Some(("hello".to_string(), 7)));
+        assert_eq!(hot.get(&(2,)).unwrap(), None);
+        let records: Vec<((u64,), (String, u64))> =
+            hot.iter().unwrap().map(|r| r.unwrap()).collect();
+        assert_eq!(records, vec![((1,), ("hello".to_string(), 7))]);
+
+        let cursor = session.open_projection("document", &["body"], "").unwrap();
+        assert_eq!(cursor.value_format(), "S");
+    }
+
+    #[test]
+    fn test_table() {
+        let home = "target/wt_record_table";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        let users = Table::<UserRow>::create(&session).unwrap();
+
+        for (id, name) in [(2, "bob"), (1, "alice"), (3, "carol")].iter() {
+            let user = UserRow {
+                id: *id,
+                name: name.to_string(),
+                age: 20 + *id as u32,
+            };
+            users.insert(&user).unwrap();
+        }
+
+        let alice = users.get(&(1,)).unwrap().unwrap();
+        assert_eq!(alice.name, "alice");
+        assert_eq!(alice.age, 21);
+        assert_eq!(users.get(&(4,)).unwrap(), None);
+
+        let mut cursor = users.cursor().unwrap();
+        let ids: Vec<u64> = cursor.iter().unwrap().map(|r| r.unwrap().id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        assert!(users.remove(&(2,)).unwrap());
+        assert!(!users.remove(&(2,)).unwrap());
+        let names: Vec<String> = cursor
+            .iter_rev()
+            .unwrap()
+            .map(|r| r.unwrap().name)
+            .collect();
+        assert_eq!(names, vec!["carol", "alice"]);
+    }
+}
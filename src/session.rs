@@ -14,11 +14,25 @@
 
 //! A context for performing database operations.
 
-use crate::error::Result;
-use crate::{Connection, Cursor};
-use std::ffi::CString;
+use crate::backup::Backup;
+use crate::change::ChangeStream;
+use crate::checkpoint::CheckpointConfig;
+use crate::config::{quote, BackupConfig, ColgroupConfig, IndexConfig, JoinConfig};
+use crate::error::{Result, RetryPolicy};
+use crate::join::JoinCursor;
+use crate::log::LogReader;
+use crate::metadata::Object;
+use crate::statistics::{DataSourceStat, SessionStat, Statistics};
+use crate::timestamp::{
+    Timestamp, TransactionTimestamp, TransactionTimestampQuery, HEX_TIMESTAMP_SIZE,
+};
+use crate::{uri, Connection, Cursor, Transaction};
+use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
+use std::os::raw::c_char;
+use std::path::Path;
 use std::ptr;
+use std::thread;
 use wiredtiger_sys::{WT_CURSOR, WT_SESSION};
 
 macro_rules! session_api {
@@ -101,6 +115,380 @@ impl<'a> Session<'a> {
             Ok(Cursor::new_unchecked(cursor))
         }
     }
+
+    /// Open a cursor duplicating the position of `cursor`, e.g. to list the
+    /// blocks of a file of a backup cursor.
+    pub(crate) fn open_duplicate_cursor<C: AsRef<str>>(
+        &self,
+        cursor: &Cursor,
+        config: C,
+    ) -> Result<Cursor<'_>> {
+        let (session, open_cursor) = session_api!(self, open_cursor);
+
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+
+        let mut dup: *mut WT_CURSOR = ptr::null_mut();
+        unsafe {
+            wt_try!(open_cursor(
+                session,
+                ptr::null(),
+                cursor.as_ptr(),
+                c_config.as_ptr(),
+                &mut dup as *mut *mut WT_CURSOR
+            ));
+            assert!(!dup.is_null());
+            Ok(Cursor::new_unchecked(dup))
+        }
+    }
+
+    /// Open a read-only cursor on the checkpoint `name` of the object `uri`.
+    ///
+    /// Use `checkpoint::LAST_CHECKPOINT` to read the most recent unnamed
+    /// checkpoint.
+    pub fn open_checkpoint_cursor<U: AsRef<str>, N: AsRef<str>>(
+        &self,
+        uri: U,
+        name: N,
+    ) -> Result<Cursor<'_>> {
+        let config = format!("checkpoint={}", quote(name.as_ref()));
+        self.open_cursor(uri, config)
+    }
+
+    /// Create the column group `name` of the table `table`, which must list it
+    /// in its `colgroups` configuration.
+    pub fn create_colgroup<T: AsRef<str>, N: AsRef<str>>(
+        &self,
+        table: T,
+        name: N,
+        config: &ColgroupConfig,
+    ) -> Result<()> {
+        self.create(
+            uri::colgroup(table.as_ref(), name.as_ref()),
+            config.to_string(),
+        )
+    }
+
+    /// Open a cursor on the table `table` whose values are only the
+    /// `projection` columns, e.g. `table:t(a,b)`.
+    ///
+    /// Only the column groups storing these columns are read.
+    pub fn open_projection<T, S, C>(
+        &self,
+        table: T,
+        projection: &[S],
+        config: C,
+    ) -> Result<Cursor<'_>>
+    where
+        T: AsRef<str>,
+        S: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let table = uri::table(table.as_ref());
+        self.open_cursor(uri::projection(&table, projection), config)
+    }
+
+    /// Create the index `name` on the table `table`.
+    pub fn create_index<T: AsRef<str>, N: AsRef<str>>(
+        &self,
+        table: T,
+        name: N,
+        config: &IndexConfig,
+    ) -> Result<()> {
+        self.create(
+            uri::index(table.as_ref(), name.as_ref()),
+            config.to_string(),
+        )
+    }
+
+    /// Open a cursor on the index `name` of the table `table`.
+    ///
+    /// The keys of the cursor are the index columns. Its values are the
+    /// `projection` columns of the table, or all value columns of the table
+    /// if `projection` is empty.
+    pub fn open_index_cursor<T, N, S, C>(
+        &self,
+        table: T,
+        name: N,
+        projection: &[S],
+        config: C,
+    ) -> Result<Cursor<'_>>
+    where
+        T: AsRef<str>,
+        N: AsRef<str>,
+        S: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let index = uri::index(table.as_ref(), name.as_ref());
+        self.open_cursor(uri::projection(&index, projection), config)
+    }
+
+    /// Open a join cursor on the table `table`, which iterates over the rows
+    /// matching all conditions joined with `Session::join`.
+    ///
+    /// Its values are the `projection` columns of the table, or all value
+    /// columns of the table if `projection` is empty.
+    pub fn open_join_cursor<T: AsRef<str>, S: AsRef<str>>(
+        &self,
+        table: T,
+        projection: &[S],
+    ) -> Result<JoinCursor<'_>> {
+        let join = uri::join(table.as_ref());
+        let cursor = self.open_cursor(uri::projection(&join, projection), "")?;
+        Ok(JoinCursor::new(cursor))
+    }
+
+    /// Add a condition to `join_cursor`, comparing the rows to the key of
+    /// `ref_cursor`.
+    ///
+    /// `ref_cursor` is an index cursor of the joined table, or a cursor on the
+    /// table itself, positioned with `search` or `search_near`. The join
+    /// cursor keeps it open as long as it needs it.
+    pub fn join<'c>(
+        &self,
+        join_cursor: &mut JoinCursor<'c>,
+        ref_cursor: Cursor<'c>,
+        config: &JoinConfig,
+    ) -> Result<()> {
+        let (session, join) = session_api!(self, join);
+        let c_config = CString::new(config.to_string()).unwrap();
+        unsafe {
+            wt_try!(join(
+                session,
+                join_cursor.as_ptr(),
+                ref_cursor.as_ptr(),
+                c_config.as_ptr()
+            ));
+        }
+        join_cursor.keep(ref_cursor);
+        Ok(())
+    }
+
+    /// Open a backup cursor, which blocks checkpoints from removing the files
+    /// it lists until it is closed.
+    pub fn open_backup(&self, config: &BackupConfig) -> Result<Backup<'_>> {
+        let cursor = self.open_cursor("backup:", config.to_string())?;
+        Ok(Backup::new(self, cursor))
+    }
+
+    /// Write `message` to the log, which must be enabled.
+    pub fn log_printf(&self, message: &str) -> Result<()> {
+        let (session, log_printf) = session_api!(self, log_printf);
+        let c_message = CString::new(message.as_bytes()).unwrap();
+        unsafe {
+            wt_try!(log_printf(
+                session,
+                b"%s\0".as_ptr() as *const c_char,
+                c_message.as_ptr()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Open a reader of the log, which must be enabled.
+    pub fn open_log_reader(&self) -> Result<LogReader<'_>> {
+        let cursor = self.open_cursor("log:", "")?;
+        Ok(LogReader::new(cursor))
+    }
+
+    /// Open a stream of the changes of the tables, read from the log.
+    pub fn open_change_stream(&self) -> Result<ChangeStream<'_>> {
+        ChangeStream::new(self)
+    }
+
+    /// Describe every object of the database, as listed by the `metadata:`
+    /// cursor.
+    pub fn list_objects(&self) -> Result<Vec<Object>> {
+        let mut cursor = self.open_cursor("metadata:", "")?;
+        let mut objects = Vec::new();
+        for record in cursor.iter::<(String,), (String,)>()? {
+            let ((uri,), (config,)) = record?;
+            objects.push(Object::parse(&uri, &config)?);
+        }
+        Ok(objects)
+    }
+
+    /// Read the statistics of the data source `uri`, e.g. `table:name`.
+    pub fn statistics<U: AsRef<str>>(&self, uri: U) -> Result<Statistics<DataSourceStat>> {
+        let mut cursor = self.open_cursor(format!("statistics:{}", uri.as_ref()), "")?;
+        Statistics::read(&mut cursor)
+    }
+
+    /// Read the statistics of this session.
+    pub fn session_statistics(&self) -> Result<Statistics<SessionStat>> {
+        let mut cursor = self.open_cursor("statistics:session", "")?;
+        Statistics::read(&mut cursor)
+    }
+
+    /// The home directory of the session's connection.
+    pub(crate) fn home(&self) -> Result<&Path> {
+        let session = self.inner.expect("session is null");
+        unsafe {
+            let conn = (*session).connection;
+            let get_home = (*conn).get_home.expect("null function pointer");
+            c_path(CStr::from_ptr(get_home(conn)))
+        }
+    }
+
+    pub fn checkpoint(&self, config: &CheckpointConfig) -> Result<()> {
+        let (session, checkpoint) = session_api!(self, checkpoint);
+        let c_config = CString::new(config.to_string()).unwrap();
+        unsafe {
+            wt_try!(checkpoint(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Reset all cursors of the session and release the resources they hold.
+    pub fn reset(&mut self) -> Result<()> {
+        let (session, reset) = session_api!(self, reset);
+        unsafe {
+            wt_try!(reset(session));
+        }
+        Ok(())
+    }
+
+    pub fn begin_transaction<C: AsRef<str>>(&self, config: C) -> Result<()> {
+        let (session, begin_transaction) = session_api!(self, begin_transaction);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        unsafe {
+            wt_try!(begin_transaction(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Commit the running transaction.
+    ///
+    /// Ending the transaction resets all cursors of the session, so it takes
+    /// the session mutably and cannot happen while a cursor is alive.
+    pub fn commit_transaction<C: AsRef<str>>(&mut self, config: C) -> Result<()> {
+        let (session, commit_transaction) = session_api!(self, commit_transaction);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        unsafe {
+            wt_try!(commit_transaction(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Roll back the running transaction. Like `commit_transaction`, it
+    /// cannot happen while a cursor is alive.
+    pub fn rollback_transaction<C: AsRef<str>>(&mut self, config: C) -> Result<()> {
+        let (session, rollback_transaction) = session_api!(self, rollback_transaction);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        unsafe {
+            wt_try!(rollback_transaction(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    pub fn prepare_transaction<C: AsRef<str>>(&self, config: C) -> Result<()> {
+        let (session, prepare_transaction) = session_api!(self, prepare_transaction);
+        let c_config = CString::new(config.as_ref().as_bytes()).unwrap();
+        unsafe {
+            wt_try!(prepare_transaction(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Set a timestamp of the running transaction.
+    pub fn timestamp_transaction(&self, which: TransactionTimestamp, ts: Timestamp) -> Result<()> {
+        let (session, timestamp_transaction) = session_api!(self, timestamp_transaction);
+        let c_config = CString::new(which.config(ts)).unwrap();
+        unsafe {
+            wt_try!(timestamp_transaction(session, c_config.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Query a timestamp of the running transaction.
+    pub fn query_timestamp(&self, which: TransactionTimestampQuery) -> Result<Timestamp> {
+        let (session, query_timestamp) = session_api!(self, query_timestamp);
+        let c_config = CString::new(which.config()).unwrap();
+        let mut hex = [0 as c_char; HEX_TIMESTAMP_SIZE];
+        unsafe {
+            wt_try!(query_timestamp(
+                session,
+                hex.as_mut_ptr(),
+                c_config.as_ptr()
+            ));
+        }
+        Timestamp::from_hex_buffer(&hex)
+    }
+
+    /// Begin a transaction which is rolled back when the returned guard is
+    /// dropped without being committed.
+    ///
+    /// The guard borrows the session mutably. Cursors used in the transaction
+    /// are opened through `Transaction::session`, and must be dropped before
+    /// it ends.
+    pub fn transaction<C: AsRef<str>>(&mut self, config: C) -> Result<Transaction<'_, 'a>> {
+        self.begin_transaction(config)?;
+        Ok(Transaction::new(self))
+    }
+
+    /// Run `f` in a transaction and commit it, retrying with the default
+    /// `RetryPolicy` when `f` or the commit fails with a retryable error.
+    ///
+    /// `config` is used to begin the transaction. If `f` fails, the
+    /// transaction is rolled back.
+    pub fn with_transaction<C, T, F>(&mut self, config: C, f: F) -> Result<T>
+    where
+        C: AsRef<str>,
+        F: FnMut(&mut Transaction<'_, 'a>) -> Result<T>,
+    {
+        self.with_transaction_retry(config, &RetryPolicy::default(), f)
+    }
+
+    /// Like `with_transaction`, but retries according to `policy`.
+    pub fn with_transaction_retry<C, T, F>(
+        &mut self,
+        config: C,
+        policy: &RetryPolicy,
+        mut f: F,
+    ) -> Result<T>
+    where
+        C: AsRef<str>,
+        F: FnMut(&mut Transaction<'_, 'a>) -> Result<T>,
+    {
+        let config = config.as_ref();
+        let mut attempt = 0;
+        loop {
+            let mut txn = self.transaction(config)?;
+            let result = match f(&mut txn) {
+                Ok(value) => txn.commit("").map(|_| value),
+                Err(error) => {
+                    drop(txn);
+                    Err(error)
+                }
+            };
+
+            match result {
+                Err(ref error) if error.is_retryable() && attempt < policy.max_retries => {
+                    attempt += 1;
+                    thread::sleep(policy.backoff(attempt));
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A path returned by WiredTiger, which need not be UTF-8 on Unix.
+#[cfg(unix)]
+fn c_path(path: &CStr) -> Result<&Path> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    Ok(Path::new(OsStr::from_bytes(path.to_bytes())))
+}
+
+/// A path returned by WiredTiger, which must be UTF-8 outside Unix.
+#[cfg(not(unix))]
+fn c_path(path: &CStr) -> Result<&Path> {
+    use crate::error::{Error, EINVAL};
+
+    path.to_str()
+        .map(Path::new)
+        .map_err(|_| Error::System(EINVAL))
 }
 
 impl<'a> Drop for Session<'a> {
@@ -116,8 +504,16 @@ impl<'a> Drop for Session<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::checkpoint;
     use test_utils;
 
+    fn put<V: crate::pack::Pack + ?Sized>(session: &Session, key: &str, value: &V) {
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        cursor.set_key(key).unwrap();
+        cursor.set_value(value).unwrap();
+        cursor.insert().unwrap();
+    }
+
     #[test]
     fn test_open_session() {
         let home = "target/wt_open_session";
@@ -138,4 +534,173 @@ mod tests {
             .unwrap();
         session.drop("table:test_table", "").unwrap();
     }
+
+    #[test]
+    fn test_transaction() {
+        let home = "target/wt_transaction";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let mut session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=S")
+            .unwrap();
+
+        session.begin_transaction("").unwrap();
+        put(&session, "a", "1");
+        session.commit_transaction("").unwrap();
+
+        session.begin_transaction("").unwrap();
+        put(&session, "b", "2");
+        session.rollback_transaction("").unwrap();
+
+        {
+            let txn = session.transaction("").unwrap();
+            put(txn.session(), "c", "3");
+            txn.commit("").unwrap();
+        }
+
+        {
+            let txn = session.transaction("").unwrap();
+            put(txn.session(), "d", "4");
+        }
+
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        let keys: Vec<String> = cursor
+            .iter::<String, String>()
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(keys, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_with_transaction() {
+        use crate::error::Error;
+
+        let home = "target/wt_with_transaction";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let mut session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=q")
+            .unwrap();
+
+        let mut attempts = 0;
+        let value = session
+            .with_transaction("", |txn| {
+                attempts += 1;
+                put(txn.session(), "a", &attempts);
+                if attempts < 3 {
+                    Err(Error::Rollback)
+                } else {
+                    Ok(attempts)
+                }
+            })
+            .unwrap();
+        assert_eq!(value, 3);
+        {
+            let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+            cursor.set_key("a").unwrap();
+            assert!(cursor.search().unwrap());
+            assert_eq!(cursor.get_value::<i64>().unwrap(), 3);
+        }
+
+        let mut attempts = 0;
+        let result: Result<()> = session.with_transaction_retry("", &RetryPolicy::never(), |_| {
+            attempts += 1;
+            Err(Error::Rollback)
+        });
+        assert!(matches!(result, Err(Error::Rollback)));
+        assert_eq!(attempts, 1);
+
+        let mut attempts = 0;
+        let result: Result<()> = session.with_transaction("", |_| {
+            attempts += 1;
+            Err(Error::NotFound)
+        });
+        assert!(matches!(result, Err(Error::NotFound)));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_transaction_timestamp() {
+        let home = "target/wt_transaction_timestamp";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let mut session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=S")
+            .unwrap();
+
+        session.begin_transaction("").unwrap();
+        session
+            .timestamp_transaction(TransactionTimestamp::Commit, Timestamp(10))
+            .unwrap();
+        put(&session, "a", "1");
+        let ts = session
+            .query_timestamp(TransactionTimestampQuery::Commit)
+            .unwrap();
+        assert_eq!(ts, Timestamp(10));
+        session.commit_transaction("").unwrap();
+
+        session
+            .begin_transaction(format!("read_timestamp={}", Timestamp(5)))
+            .unwrap();
+        {
+            let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+            cursor.set_key("a").unwrap();
+            assert!(!cursor.search().unwrap());
+        }
+        session.rollback_transaction("").unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let home = "target/wt_checkpoint";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:test_table", "key_format=S,value_format=S")
+            .unwrap();
+
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        cursor.set_key("a").unwrap();
+        cursor.set_value("1").unwrap();
+        cursor.insert().unwrap();
+        cursor.close().unwrap();
+        session
+            .checkpoint(&CheckpointConfig::new().name("first"))
+            .unwrap();
+
+        let mut cursor = session.open_cursor("table:test_table", "").unwrap();
+        cursor.set_key("a").unwrap();
+        cursor.set_value("2").unwrap();
+        cursor.update().unwrap();
+        cursor.close().unwrap();
+        session.checkpoint(&CheckpointConfig::new()).unwrap();
+
+        let mut cursor = session
+            .open_checkpoint_cursor("table:test_table", "first")
+            .unwrap();
+        cursor.set_key("a").unwrap();
+        assert!(cursor.search().unwrap());
+        assert_eq!(cursor.get_value::<String>().unwrap(), "1");
+        cursor.close().unwrap();
+
+        let mut cursor = session
+            .open_checkpoint_cursor("table:test_table", checkpoint::LAST_CHECKPOINT)
+            .unwrap();
+        cursor.set_key("a").unwrap();
+        assert!(cursor.search().unwrap());
+        assert_eq!(cursor.get_value::<String>().unwrap(), "2");
+        cursor.close().unwrap();
+
+        session
+            .checkpoint(&CheckpointConfig::new().drop_checkpoint("first"))
+            .unwrap();
+        assert!(session
+            .open_checkpoint_cursor("table:test_table", "first")
+            .is_err());
+    }
 }
@@ -0,0 +1,185 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoded contents of `statistics:` cursors.
+//!
+//! The statistics IDs are generated from the `WT_STAT_*` constants of
+//! WiredTiger's header, e.g. `WT_STAT_CONN_CACHE_BYTES_INUSE` is
+//! `ConnectionStat::CacheBytesInuse`. Statistics must be enabled with the
+//! `statistics` configuration of the connection.
+
+use crate::error::Result;
+use crate::Cursor;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::iter::FromIterator;
+
+/// The ID of a statistic of some category.
+pub trait StatId: Debug + Copy + Ord {
+    fn from_id(id: i32) -> Option<Self>;
+    fn id(self) -> i32;
+    /// The name of the constant without its prefix, in lower case, e.g.
+    /// `cache_bytes_inuse`.
+    fn name(self) -> &'static str;
+}
+
+macro_rules! stat_ids {
+    ($($category: ident: [$(($variant: ident, $id: ident, $name: expr),)*],)*) => {
+        $(
+            #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub enum $category {
+                $($variant,)*
+            }
+
+            impl StatId for $category {
+                fn from_id(id: i32) -> Option<Self> {
+                    match id as u32 {
+                        $(wiredtiger_sys::$id => Some($category::$variant),)*
+                        _ => None,
+                    }
+                }
+
+                fn id(self) -> i32 {
+                    match self {
+                        $($category::$variant => wiredtiger_sys::$id as i32,)*
+                    }
+                }
+
+                fn name(self) -> &'static str {
+                    match self {
+                        $($category::$variant => $name,)*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+wiredtiger_sys::wiredtiger_stats!(stat_ids);
+
+/// A statistic as reported by WiredTiger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stat {
+    pub description: String,
+    pub value: i64,
+    /// The value formatted for display, e.g. with units.
+    pub printable: String,
+}
+
+/// The statistics of a `statistics:` cursor, keyed by their IDs.
+#[derive(Debug, Clone)]
+pub struct Statistics<S> {
+    stats: BTreeMap<S, Stat>,
+}
+
+impl<S: StatId> Statistics<S> {
+    /// Read all statistics of `cursor`, skipping the ones of other
+    /// categories.
+    pub(crate) fn read(cursor: &mut Cursor) -> Result<Self> {
+        let mut stats = BTreeMap::new();
+        for record in cursor.iter::<(i32,), (String, String, i64)>()? {
+            let ((id,), (description, printable, value)) = record?;
+            if let Some(id) = S::from_id(id) {
+                let stat = Stat {
+                    description,
+                    value,
+                    printable,
+                };
+                stats.insert(id, stat);
+            }
+        }
+        Ok(Self { stats })
+    }
+
+    #[inline]
+    pub fn get(&self, id: S) -> Option<&Stat> {
+        self.stats.get(&id)
+    }
+
+    #[inline]
+    pub fn value(&self, id: S) -> Option<i64> {
+        self.get(id).map(|stat| stat.value)
+    }
+
+    /// Iterate over the statistics in the order of their IDs.
+    pub fn iter(&self) -> impl Iterator<Item = (S, &Stat)> {
+        self.stats.iter().map(|(id, stat)| (*id, stat))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.stats.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+}
+
+impl<S: StatId> FromIterator<(S, Stat)> for Statistics<S> {
+    fn from_iter<I: IntoIterator<Item = (S, Stat)>>(iter: I) -> Self {
+        Self {
+            stats: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Connection;
+    use test_utils;
+
+    #[test]
+    fn test_stat_id() {
+        let id = ConnectionStat::CacheBytesInuse;
+        assert_eq!(
+            id.id(),
+            wiredtiger_sys::WT_STAT_CONN_CACHE_BYTES_INUSE as i32
+        );
+        assert_eq!(ConnectionStat::from_id(id.id()), Some(id));
+        assert_eq!(id.name(), "cache_bytes_inuse");
+        assert_eq!(DataSourceStat::from_id(id.id()), None);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let home = "target/wt_statistics";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create,statistics=(fast)").unwrap();
+        let session = conn.open_session("").unwrap();
+        session
+            .create("table:stats", "key_format=S,value_format=S")
+            .unwrap();
+        let mut cursor = session.open_cursor("table:stats", "").unwrap();
+        cursor.set_key("key").unwrap();
+        cursor.set_value("value").unwrap();
+        cursor.insert().unwrap();
+
+        let stats = conn.statistics().unwrap();
+        assert!(!stats.is_empty());
+        let max = stats.get(ConnectionStat::CacheBytesMax).unwrap();
+        assert!(max.value > 0);
+        assert!(!max.description.is_empty());
+
+        let stats = session.statistics("table:stats").unwrap();
+        assert_eq!(stats.value(DataSourceStat::CursorInsert), Some(1));
+
+        let stats = session.session_statistics().unwrap();
+        assert!(stats
+            .iter()
+            .all(|(id, _)| SessionStat::from_id(id.id()) == Some(id)));
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timestamps of transactions and of the database.
+
+use crate::error::{Error, Result, EINVAL};
+use std::ffi::CStr;
+use std::fmt::Formatter;
+use std::os::raw::c_char;
+use std::str::FromStr;
+
+/// Size of the buffer WiredTiger writes a queried timestamp into.
+pub(crate) const HEX_TIMESTAMP_SIZE: usize = 2 * std::mem::size_of::<u64>() + 1;
+
+/// A WiredTiger timestamp.
+///
+/// It is formatted and parsed as the hexadecimal string WiredTiger uses in
+/// configuration strings. A zero timestamp means "not set".
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    #[inline]
+    pub fn is_set(self) -> bool {
+        self.0 != 0
+    }
+
+    /// Parse the timestamp WiredTiger wrote into `buffer`.
+    pub(crate) fn from_hex_buffer(buffer: &[c_char; HEX_TIMESTAMP_SIZE]) -> Result<Self> {
+        let hex = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        hex.to_str()
+            .map_err(|_| Error::System(EINVAL))
+            .and_then(Timestamp::from_str)
+    }
+}
+
+impl From<u64> for Timestamp {
+    #[inline]
+    fn from(ts: u64) -> Self {
+        Timestamp(ts)
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:x}", self.0)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        u64::from_str_radix(s, 16)
+            .map(Timestamp)
+            .map_err(|_| Error::System(EINVAL))
+    }
+}
+
+/// The timestamps of a transaction that can be set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransactionTimestamp {
+    Commit,
+    Durable,
+    Prepare,
+    Read,
+}
+
+impl TransactionTimestamp {
+    pub(crate) fn config(self, ts: Timestamp) -> String {
+        let name = match self {
+            TransactionTimestamp::Commit => "commit_timestamp",
+            TransactionTimestamp::Durable => "durable_timestamp",
+            TransactionTimestamp::Prepare => "prepare_timestamp",
+            TransactionTimestamp::Read => "read_timestamp",
+        };
+        format!("{}={}", name, ts)
+    }
+}
+
+/// The timestamps of a transaction that can be queried.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransactionTimestampQuery {
+    Commit,
+    FirstCommit,
+    Prepare,
+    Read,
+}
+
+impl TransactionTimestampQuery {
+    pub(crate) fn config(self) -> &'static str {
+        match self {
+            TransactionTimestampQuery::Commit => "get=commit",
+            TransactionTimestampQuery::FirstCommit => "get=first_commit",
+            TransactionTimestampQuery::Prepare => "get=prepare",
+            TransactionTimestampQuery::Read => "get=read",
+        }
+    }
+}
+
+/// The global timestamps of a connection that can be set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlobalTimestamp {
+    Durable,
+    Oldest,
+    Stable,
+}
+
+impl GlobalTimestamp {
+    pub(crate) fn config(self, ts: Timestamp) -> String {
+        let name = match self {
+            GlobalTimestamp::Durable => "durable_timestamp",
+            GlobalTimestamp::Oldest => "oldest_timestamp",
+            GlobalTimestamp::Stable => "stable_timestamp",
+        };
+        format!("{}={}", name, ts)
+    }
+}
+
+/// The global timestamps of a connection that can be queried.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlobalTimestampQuery {
+    AllDurable,
+    LastCheckpoint,
+    Oldest,
+    OldestReader,
+    Pinned,
+    Recovery,
+    Stable,
+}
+
+impl GlobalTimestampQuery {
+    pub(crate) fn config(self) -> &'static str {
+        match self {
+            GlobalTimestampQuery::AllDurable => "get=all_durable",
+            GlobalTimestampQuery::LastCheckpoint => "get=last_checkpoint",
+            GlobalTimestampQuery::Oldest => "get=oldest_timestamp",
+            GlobalTimestampQuery::OldestReader => "get=oldest_reader",
+            GlobalTimestampQuery::Pinned => "get=pinned",
+            GlobalTimestampQuery::Recovery => "get=recovery",
+            GlobalTimestampQuery::Stable => "get=stable_timestamp",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_hex() {
+        assert_eq!(Timestamp(0x2a).to_string(), "2a");
+        assert_eq!("ff".parse::<Timestamp>().unwrap(), Timestamp(255));
+        assert!("xyz".parse::<Timestamp>().is_err());
+        assert_eq!(
+            TransactionTimestamp::Commit.config(Timestamp(16)),
+            "commit_timestamp=10"
+        );
+    }
+}
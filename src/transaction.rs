@@ -0,0 +1,73 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transactions on a session.
+
+use crate::error::Result;
+use crate::Session;
+
+/// A running transaction, which is rolled back on drop unless it has been
+/// committed or rolled back explicitly.
+///
+/// Cursors opened through `session` borrow the guard, so the transaction
+/// cannot end while they, or the slices they return, are alive.
+pub struct Transaction<'s, 'a> {
+    session: &'s mut Session<'a>,
+    finished: bool,
+}
+
+impl<'s, 'a> Transaction<'s, 'a> {
+    pub(crate) fn new(session: &'s mut Session<'a>) -> Self {
+        Self {
+            session,
+            finished: false,
+        }
+    }
+
+    /// The session running the transaction.
+    #[inline]
+    pub fn session(&self) -> &Session<'a> {
+        self.session
+    }
+
+    /// Prepare the transaction for a two-phase commit. It must still be
+    /// committed or rolled back afterwards.
+    pub fn prepare<C: AsRef<str>>(&mut self, config: C) -> Result<()> {
+        self.session.prepare_transaction(config)
+    }
+
+    /// Commit the transaction.
+    ///
+    /// WiredTiger rolls the transaction back if the commit fails.
+    pub fn commit<C: AsRef<str>>(mut self, config: C) -> Result<()> {
+        self.finished = true;
+        self.session.commit_transaction(config)
+    }
+
+    /// Roll back the transaction.
+    pub fn rollback<C: AsRef<str>>(mut self, config: C) -> Result<()> {
+        self.finished = true;
+        self.session.rollback_transaction(config)
+    }
+}
+
+impl<'s, 'a> Drop for Transaction<'s, 'a> {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Err(error) = self.session.rollback_transaction("") {
+                eprintln!("error happened when auto rollback transaction: {}", error);
+            }
+        }
+    }
+}
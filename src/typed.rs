@@ -0,0 +1,307 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tables of serde types.
+//!
+//! A `TypedTable<K, V>` stores its keys and values as byte arrays in a
+//! `key_format=u,value_format=u` table. Keys are encoded with the `ordered`
+//! format, so the table is ordered like `K`. Values are encoded with a
+//! `Codec`, which is the `ordered` format by default.
+
+use crate::error::Result;
+use crate::{ordered, Cursor, Iter, Session};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// Encodes and decodes the values of a `TypedTable`.
+pub trait Codec {
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>>;
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T>;
+}
+
+/// The `ordered` format as a `Codec`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OrderedCodec;
+
+impl Codec for OrderedCodec {
+    #[inline]
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        ordered::to_vec(value)
+    }
+
+    #[inline]
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        ordered::from_slice(bytes)
+    }
+}
+
+const TABLE_CONFIG: &str = "key_format=u,value_format=u";
+
+/// A table of `K` keys and `V` values.
+pub struct TypedTable<'s, K, V, C = OrderedCodec> {
+    session: &'s Session<'s>,
+    uri: String,
+    codec: C,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'s, K, V> TypedTable<'s, K, V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    /// Create the table `table:<name>` if it does not exist, and open it.
+    pub fn create(session: &'s Session<'s>, name: &str) -> Result<Self> {
+        Self::create_with_codec(session, name, OrderedCodec)
+    }
+}
+
+impl<'s, K, V, C> TypedTable<'s, K, V, C>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+    C: Codec,
+{
+    /// Create the table `table:<name>` if it does not exist, and open it with
+    /// `codec` for values.
+    pub fn create_with_codec(session: &'s Session<'s>, name: &str, codec: C) -> Result<Self> {
+        let uri = format!("table:{}", name);
+        session.create(&uri, TABLE_CONFIG)?;
+        Ok(Self {
+            session,
+            uri,
+            codec,
+            marker: PhantomData,
+        })
+    }
+
+    /// The URI of the table.
+    #[inline]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Open a cursor on the table.
+    pub fn cursor(&self) -> Result<TypedCursor<'_, K, V, C>> {
+        Ok(TypedCursor {
+            cursor: self.session.open_cursor(&self.uri, "")?,
+            codec: &self.codec,
+            marker: PhantomData,
+        })
+    }
+
+    /// Insert or overwrite the value of `key`.
+    pub fn insert(&self, key: &K, value: &V) -> Result<()> {
+        self.cursor()?.insert(key, value)
+    }
+
+    /// Get the value of `key`.
+    pub fn get(&self, key: &K) -> Result<Option<V>> {
+        self.cursor()?.get(key)
+    }
+
+    /// Remove `key`. Returns `false` if it did not exist.
+    pub fn remove(&self, key: &K) -> Result<bool> {
+        self.cursor()?.remove(key)
+    }
+}
+
+/// A cursor on a `TypedTable`.
+pub struct TypedCursor<'t, K, V, C> {
+    cursor: Cursor<'t>,
+    codec: &'t C,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'t, K, V, C> TypedCursor<'t, K, V, C>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+    C: Codec,
+{
+    fn set_key(&mut self, key: &K) -> Result<()> {
+        let key = ordered::to_vec(key)?;
+        self.cursor.set_raw_key(&key);
+        Ok(())
+    }
+
+    /// Insert or overwrite the value of `key`.
+    pub fn insert(&mut self, key: &K, value: &V) -> Result<()> {
+        self.set_key(key)?;
+        let value = self.codec.encode(value)?;
+        self.cursor.set_raw_value(&value);
+        self.cursor.insert()
+    }
+
+    /// Get the value of `key`.
+    pub fn get(&mut self, key: &K) -> Result<Option<V>> {
+        self.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(None);
+        }
+        let value = self.codec.decode(self.cursor.get_raw_value()?)?;
+        self.cursor.reset()?;
+        Ok(Some(value))
+    }
+
+    /// Remove `key`. Returns `false` if it did not exist.
+    pub fn remove(&mut self, key: &K) -> Result<bool> {
+        self.set_key(key)?;
+        if !self.cursor.search()? {
+            return Ok(false);
+        }
+        self.cursor.remove()?;
+        Ok(true)
+    }
+
+    /// Iterate over all records in key order.
+    pub fn iter(&mut self) -> Result<TypedIter<'_, 't, K, V, C>> {
+        let codec = self.codec;
+        Ok(TypedIter {
+            inner: self.cursor.iter()?,
+            codec,
+            marker: PhantomData,
+        })
+    }
+
+    /// Iterate over all records in reverse key order.
+    pub fn iter_rev(&mut self) -> Result<TypedIter<'_, 't, K, V, C>> {
+        let codec = self.codec;
+        Ok(TypedIter {
+            inner: self.cursor.iter_rev()?,
+            codec,
+            marker: PhantomData,
+        })
+    }
+
+    /// Iterate over the records whose keys are within `range`, in key order.
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> Result<TypedIter<'_, 't, K, V, C>> {
+        let range = encode_range(range)?;
+        let codec = self.codec;
+        Ok(TypedIter {
+            inner: self.cursor.range(range)?,
+            codec,
+            marker: PhantomData,
+        })
+    }
+
+    /// Iterate over the records whose keys are within `range`, in reverse key
+    /// order.
+    pub fn range_rev<R: RangeBounds<K>>(&mut self, range: R) -> Result<TypedIter<'_, 't, K, V, C>> {
+        let range = encode_range(range)?;
+        let codec = self.codec;
+        Ok(TypedIter {
+            inner: self.cursor.range_rev(range)?,
+            codec,
+            marker: PhantomData,
+        })
+    }
+}
+
+type EncodedRange = (Bound<Vec<u8>>, Bound<Vec<u8>>);
+
+fn encode_range<K: Serialize, R: RangeBounds<K>>(range: R) -> Result<EncodedRange> {
+    let encode = |bound: Bound<&K>| -> Result<Bound<Vec<u8>>> {
+        Ok(match bound {
+            Bound::Included(key) => Bound::Included(ordered::to_vec(key)?),
+            Bound::Excluded(key) => Bound::Excluded(ordered::to_vec(key)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    };
+    Ok((encode(range.start_bound())?, encode(range.end_bound())?))
+}
+
+/// An iterator over the records of a `TypedCursor`.
+pub struct TypedIter<'c, 't, K, V, C> {
+    inner: Iter<'c, 't, Vec<u8>, Vec<u8>>,
+    codec: &'t C,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'c, 't, K, V, C> Iterator for TypedIter<'c, 't, K, V, C>
+where
+    K: DeserializeOwned,
+    V: DeserializeOwned,
+    C: Codec,
+{
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let codec = self.codec;
+        let decode = |(key, value): (Vec<u8>, Vec<u8>)| -> Result<(K, V)> {
+            Ok((ordered::from_slice(&key)?, codec.decode(&value)?))
+        };
+        self.inner.next().map(|record| record.and_then(decode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Connection;
+    use serde::Deserialize;
+    use test_utils;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    #[test]
+    fn test_typed_table() {
+        let home = "target/wt_typed_table";
+        test_utils::make_work_dir(home, true);
+        let conn = Connection::open(home, "create").unwrap();
+        let session = conn.open_session("").unwrap();
+        let table: TypedTable<(String, i64), User> = TypedTable::create(&session, "users").unwrap();
+
+        for (i, name) in ["carol", "alice", "bob"].iter().enumerate() {
+            let user = User {
+                name: name.to_string(),
+                age: 20 + i as u32,
+            };
+            table
+                .insert(&(name.to_string(), -(i as i64)), &user)
+                .unwrap();
+        }
+
+        let alice = table.get(&("alice".to_string(), -1)).unwrap().unwrap();
+        assert_eq!(alice.age, 21);
+        assert_eq!(table.get(&("alice".to_string(), 0)).unwrap(), None);
+
+        let mut cursor = table.cursor().unwrap();
+        let names: Vec<String> = cursor.iter().unwrap().map(|r| r.unwrap().1.name).collect();
+        assert_eq!(names, vec!["alice", "bob", "carol"]);
+
+        let start = ("b".to_string(), i64::MIN);
+        let names: Vec<String> = cursor
+            .range(start..)
+            .unwrap()
+            .map(|r| r.unwrap().0 .0)
+            .collect();
+        assert_eq!(names, vec!["bob", "carol"]);
+
+        assert!(table.remove(&("bob".to_string(), -2)).unwrap());
+        assert!(!table.remove(&("bob".to_string(), -2)).unwrap());
+        let names: Vec<String> = cursor
+            .iter_rev()
+            .unwrap()
+            .map(|r| r.unwrap().1.name)
+            .collect();
+        assert_eq!(names, vec!["carol", "alice"]);
+    }
+}
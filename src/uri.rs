@@ -0,0 +1,63 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Build the URIs of data sources.
+
+/// The URI of the table `name`: `table:<name>`.
+pub fn table(name: &str) -> String {
+    format!("table:{}", name)
+}
+
+/// The URI of the column group `name` of `table`: `colgroup:<table>:<name>`.
+pub fn colgroup(table: &str, name: &str) -> String {
+    format!("colgroup:{}:{}", table, name)
+}
+
+/// The URI of the index `name` on `table`: `index:<table>:<name>`.
+pub fn index(table: &str, name: &str) -> String {
+    format!("index:{}:{}", table, name)
+}
+
+/// The URI of a join cursor on the table `name`: `join:table:<name>`.
+pub fn join(table: &str) -> String {
+    format!("join:table:{}", table)
+}
+
+/// Append the projection `columns` to a table or index URI, e.g.
+/// `table:t(a,b)`. No projection is appended if `columns` is empty.
+pub fn projection<S: AsRef<str>>(uri: &str, columns: &[S]) -> String {
+    if columns.is_empty() {
+        return uri.to_string();
+    }
+    let columns: Vec<&str> = columns.iter().map(|c| c.as_ref()).collect();
+    format!("{}({})", uri, columns.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri() {
+        assert_eq!(table("t"), "table:t");
+        assert_eq!(colgroup("t", "cold"), "colgroup:t:cold");
+        assert_eq!(index("t", "idx"), "index:t:idx");
+        assert_eq!(join("t"), "join:table:t");
+        assert_eq!(
+            projection(&index("t", "idx"), &["a", "b"]),
+            "index:t:idx(a,b)"
+        );
+        assert_eq!(projection::<&str>(&table("t"), &[]), "table:t");
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2020 David Li
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Derive macro for `wiredtiger::record::Record`.
+//!
+//! ```ignore
+//! #[derive(WtRecord)]
+//! #[wt(table = "users")]
+//! struct User {
+//!     #[wt(key)]
+//!     id: u64,
+//!     #[wt(column = "user_name", colgroup = "main")]
+//!     name: String,
+//!     #[wt(colgroup = "main")]
+//!     age: u32,
+//!     #[wt(colgroup = "cold")]
+//!     bio: String,
+//! }
+//! ```
+//!
+//! Fields marked `#[wt(key)]` form the key, the other fields form the value,
+//! both in declaration order. Every field type must implement `Format`,
+//! `Pack` and `Unpack`. The table name defaults to the struct name in snake
+//! case and a column defaults to the field name.
+//!
+//! If any value field names a column group, all of them must, and the table
+//! is created with these column groups.
+//!
+//! The key and the value are tuples of the field types, so each holds at most
+//! 8 fields, the largest tuples `Format`, `Pack` and `Unpack` are implemented
+//! for.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Result, Type};
+
+/// The most fields of the key or the value, as tuples are packed up to this
+/// size.
+const MAX_FIELDS: usize = 8;
+
+#[proc_macro_derive(WtRecord, attributes(wt))]
+pub fn derive_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Column {
+    ident: Ident,
+    ty: Type,
+    name: String,
+    colgroup: Option<String>,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "WtRecord cannot be derived for generic structs",
+        ));
+    }
+
+    let mut table = snake_case(&name.to_string());
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("wt")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported wt attribute"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    name,
+                    "WtRecord can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "WtRecord can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let mut column = Column {
+            name: ident.to_string(),
+            ident,
+            ty: field.ty.clone(),
+            colgroup: None,
+        };
+        let mut key = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("wt")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = true;
+                    Ok(())
+                } else if meta.path.is_ident("column") {
+                    column.name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("colgroup") {
+                    column.colgroup = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported wt attribute"))
+                }
+            })?;
+        }
+        if key {
+            if column.colgroup.is_some() {
+                return Err(Error::new_spanned(
+                    &column.ident,
+                    "key fields cannot name a column group",
+                ));
+            }
+            keys.push(column);
+        } else {
+            values.push(column);
+        }
+    }
+    if keys.is_empty() {
+        return Err(Error::new_spanned(
+            name,
+            "WtRecord requires at least one #[wt(key)] field",
+        ));
+    }
+    if values.is_empty() {
+        return Err(Error::new_spanned(
+            name,
+            "WtRecord requires at least one value field",
+        ));
+    }
+    for (part, columns) in &[("key", &keys), ("value", &values)] {
+        if let Some(column) = columns.get(MAX_FIELDS) {
+            return Err(Error::new_spanned(
+                &column.ident,
+                format!("WtRecord supports at most {} {} fields", MAX_FIELDS, part),
+            ));
+        }
+    }
+
+    let mut colgroups: Vec<(String, Vec<String>)> = Vec::new();
+    if values.iter().any(|c| c.colgroup.is_some()) {
+        for column in &values {
+            let colgroup = match &column.colgroup {
+                Some(colgroup) => colgroup,
+                None => {
+                    return Err(Error::new_spanned(
+                        &column.ident,
+                        "every value field must name a column group when any does",
+                    ))
+                }
+            };
+            match colgroups.iter_mut().find(|(name, _)| name == colgroup) {
+                Some((_, columns)) => columns.push(column.name.clone()),
+                None => colgroups.push((colgroup.clone(), vec![column.name.clone()])),
+            }
+        }
+    }
+    let colgroup_names: Vec<_> = colgroups.iter().map(|(name, _)| name).collect();
+    let colgroup_columns: Vec<_> = colgroups.iter().map(|(_, columns)| columns).collect();
+
+    let key_types: Vec<_> = keys.iter().map(|c| &c.ty).collect();
+    let key_idents: Vec<_> = keys.iter().map(|c| &c.ident).collect();
+    let key_names: Vec<_> = keys.iter().map(|c| &c.name).collect();
+    let value_types: Vec<_> = values.iter().map(|c| &c.ty).collect();
+    let value_idents: Vec<_> = values.iter().map(|c| &c.ident).collect();
+    let value_names: Vec<_> = values.iter().map(|c| &c.name).collect();
+
+    Ok(quote! {
+        impl ::wiredtiger::record::Record for #name {
+            type Key = (#(#key_types,)*);
+            type Value = (#(#value_types,)*);
+
+            const TABLE: &'static str = #table;
+            const KEY_COLUMNS: &'static [&'static str] = &[#(#key_names),*];
+            const VALUE_COLUMNS: &'static [&'static str] = &[#(#value_names),*];
+            const COLGROUPS: &'static [(&'static str, &'static [&'static str])] =
+                &[#((#colgroup_names, &[#(#colgroup_columns),*])),*];
+
+            fn key_format() -> ::std::string::String {
+                [#(<#key_types as ::wiredtiger::pack::Format>::FORMAT),*].concat()
+            }
+
+            fn value_format() -> ::std::string::String {
+                [#(<#value_types as ::wiredtiger::pack::Format>::FORMAT),*].concat()
+            }
+
+            fn pack_key<'a>(&'a self, packer: &mut ::wiredtiger::pack::Packer<'a>) {
+                #(::wiredtiger::pack::Pack::pack(&self.#key_idents, packer);)*
+            }
+
+            fn pack_value<'a>(&'a self, packer: &mut ::wiredtiger::pack::Packer<'a>) {
+                #(::wiredtiger::pack::Pack::pack(&self.#value_idents, packer);)*
+            }
+
+            fn from_parts(key: Self::Key, value: Self::Value) -> Self {
+                let (#(#key_idents,)*) = key;
+                let (#(#value_idents,)*) = value;
+                #name {
+                    #(#key_idents,)*
+                    #(#value_idents,)*
+                }
+            }
+        }
+    })
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -14,6 +14,7 @@
 
 use bindgen;
 use std::env;
+use std::fs;
 use std::path::PathBuf;
 
 fn main() {
@@ -26,7 +27,7 @@ fn main() {
     println!("cargo:rerun-if-changed={}/include", wt_home);
     println!("cargo:rerun-if-env-changed=WIREDTIGER_HOME");
 
-    bindgen::Builder::default()
+    let bindings = bindgen::Builder::default()
         .clang_arg(format!("-I{}/include", wt_home))
         .header("wrapper.h")
         .whitelist_var("WIREDTIGER.*")
@@ -37,7 +38,60 @@ fn main() {
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
         .rustfmt_bindings(true)
         .generate()
-        .expect("Unable to generate wiredtiger's bindings")
+        .expect("Unable to generate wiredtiger's bindings");
+    bindings
         .write_to_file(PathBuf::from("src/bindings.rs"))
         .expect("Unable to write wiredtiger's bindings");
+
+    fs::write("src/stats.rs", stats(&bindings.to_string()))
+        .expect("Unable to write wiredtiger's statistics");
+}
+
+/// Statistics categories, as the enum listing them and the prefix of their
+/// constants.
+const STAT_CATEGORIES: [(&str, &str); 3] = [
+    ("ConnectionStat", "WT_STAT_CONN_"),
+    ("DataSourceStat", "WT_STAT_DSRC_"),
+    ("SessionStat", "WT_STAT_SESSION_"),
+];
+
+/// Generate the `wiredtiger_stats!` macro from the `WT_STAT_*` constants of
+/// `bindings`, e.g. `WT_STAT_CONN_CACHE_BYTES_INUSE` becomes
+/// `(CacheBytesInuse, WT_STAT_CONN_CACHE_BYTES_INUSE, "cache_bytes_inuse")`.
+fn stats(bindings: &str) -> String {
+    let constants: Vec<&str> = bindings
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("pub const WT_STAT_"))
+        .filter_map(|line| line["pub const ".len()..].split(':').next())
+        .collect();
+
+    let mut out = String::new();
+    out.push_str("// Generated by build.rs from the WT_STAT_* constants.\n\n");
+    out.push_str("/// Call `$callback!` with the statistics of each category, as\n");
+    out.push_str("/// `(Variant, CONSTANT, \"name\")` tuples.\n");
+    out.push_str("#[macro_export]\n");
+    out.push_str("macro_rules! wiredtiger_stats {\n");
+    out.push_str("    ($callback: ident) => {\n");
+    out.push_str("        $callback! {\n");
+    for (category, prefix) in STAT_CATEGORIES.iter() {
+        out.push_str(&format!("            {}: [\n", category));
+        for constant in constants.iter().filter(|c| c.starts_with(prefix)) {
+            let name = constant[prefix.len()..].to_lowercase();
+            let variant: String = name
+                .split('_')
+                .filter(|word| !word.is_empty())
+                .map(|word| word[..1].to_uppercase() + &word[1..])
+                .collect();
+            out.push_str(&format!(
+                "                ({}, {}, \"{}\"),\n",
+                variant, constant, name
+            ));
+        }
+        out.push_str("            ],\n");
+    }
+    out.push_str("        }\n");
+    out.push_str("    };\n");
+    out.push_str("}\n");
+    out
 }
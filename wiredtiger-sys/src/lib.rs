@@ -21,5 +21,6 @@
 #![allow(clippy::unreadable_literal)]
 
 mod bindings;
+mod stats;
 
 pub use bindings::*;